@@ -0,0 +1,298 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Closed integer intervals and sets of them.
+
+use color_eyre::eyre::eyre;
+use color_eyre::Report;
+use num_traits::PrimInt;
+use std::cmp;
+use std::fmt;
+
+/// Closed interval `[lo, hi]`; `lo <= hi` always holds, as the fields
+/// can only be set by the constructors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    lo: T,
+    hi: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    /// Creates the interval `[lo, hi]`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi`; use [`Interval::try_new`] for bounds that
+    /// come from the input.
+    pub fn new(lo: T, hi: T) -> Interval<T> {
+        Interval::try_new(lo, hi).expect("invalid interval")
+    }
+    /// Creates the interval `[lo, hi]`, or `None` if `lo > hi`
+    pub fn try_new(lo: T, hi: T) -> Option<Interval<T>> {
+        (lo <= hi).then_some(Interval { lo, hi })
+    }
+    pub fn lo(&self) -> T {
+        self.lo
+    }
+    pub fn hi(&self) -> T {
+        self.hi
+    }
+    /// Number of integers in the interval, or `None` if it doesn't fit
+    /// in `T`, as for the full range of `T`
+    pub fn len(&self) -> Option<T> {
+        self.hi.checked_sub(&self.lo)?.checked_add(&T::one())
+    }
+    pub fn contains(&self, x: T) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        self.lo <= other.lo && other.hi <= self.hi
+    }
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        self.overlaps(other).then(|| Interval {
+            lo: cmp::max(self.lo, other.lo),
+            hi: cmp::min(self.hi, other.hi),
+        })
+    }
+}
+
+impl<T: PrimInt + fmt::Debug> TryFrom<(T, T)> for Interval<T> {
+    type Error = Report;
+    fn try_from((lo, hi): (T, T)) -> Result<Self, Self::Error> {
+        Interval::try_new(lo, hi).ok_or_else(|| eyre!("invalid interval [{:?}, {:?}]", lo, hi))
+    }
+}
+
+/// Set of integers stored as sorted, disjoint and non-adjacent intervals
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet::default()
+    }
+    /// Builds the set from already sorted intervals, merging as needed
+    fn from_sorted(sorted: impl Iterator<Item = Interval<T>>) -> IntervalSet<T> {
+        let mut intervals = Vec::<Interval<T>>::new();
+        for iv in sorted {
+            match intervals.last_mut() {
+                Some(last) if iv.lo <= last.hi.saturating_add(T::one()) => {
+                    last.hi = cmp::max(last.hi, iv.hi);
+                }
+                _ => intervals.push(iv),
+            }
+        }
+        IntervalSet { intervals }
+    }
+    pub fn insert(&mut self, iv: Interval<T>) {
+        let i = self
+            .intervals
+            .partition_point(|other| other.hi.saturating_add(T::one()) < iv.lo);
+        let mut j = i;
+        let mut merged = iv;
+        while j < self.intervals.len() && self.intervals[j].lo <= merged.hi.saturating_add(T::one())
+        {
+            merged.lo = cmp::min(merged.lo, self.intervals[j].lo);
+            merged.hi = cmp::max(merged.hi, self.intervals[j].hi);
+            j += 1;
+        }
+        self.intervals.splice(i..j, [merged]);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+    /// Total number of integers in the set, or `None` if it doesn't fit
+    /// in `T`
+    pub fn len(&self) -> Option<T> {
+        self.intervals
+            .iter()
+            .try_fold(T::zero(), |acc, iv| acc.checked_add(&iv.len()?))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+    /// Intervals between consecutive intervals of the set
+    pub fn gaps(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals.windows(2).map(|w| Interval {
+            lo: w[0].hi + T::one(),
+            hi: w[1].lo - T::one(),
+        })
+    }
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.intervals.partition_point(|iv| iv.hi < x);
+        i < self.intervals.len() && self.intervals[i].lo <= x
+    }
+    pub fn contains_interval(&self, iv: &Interval<T>) -> bool {
+        let i = self.intervals.partition_point(|other| other.hi < iv.lo);
+        i < self.intervals.len() && self.intervals[i].contains_interval(iv)
+    }
+    pub fn overlaps(&self, iv: &Interval<T>) -> bool {
+        let i = self.intervals.partition_point(|other| other.hi < iv.lo);
+        i < self.intervals.len() && self.intervals[i].overlaps(iv)
+    }
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut all = self
+            .intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        all.sort_unstable();
+        IntervalSet::from_sorted(all.into_iter())
+    }
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (&self.intervals[i], &other.intervals[j]);
+            if let Some(iv) = a.intersection(b) {
+                intervals.push(iv);
+            }
+            if a.hi < b.hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let mut j = 0;
+        for a in &self.intervals {
+            let mut lo = a.lo;
+            let mut done = false;
+            while j < other.intervals.len() && other.intervals[j].hi < lo {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].lo <= a.hi {
+                let b = &other.intervals[k];
+                if b.lo > lo {
+                    intervals.push(Interval {
+                        lo,
+                        hi: b.lo - T::one(),
+                    });
+                }
+                if b.hi >= a.hi {
+                    done = true;
+                    break;
+                }
+                lo = b.hi + T::one();
+                k += 1;
+            }
+            if !done {
+                intervals.push(Interval { lo, hi: a.hi });
+            }
+        }
+        IntervalSet { intervals }
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut all = iter.into_iter().collect::<Vec<_>>();
+        all.sort_unstable();
+        IntervalSet::from_sorted(all.into_iter())
+    }
+}
+
+impl<T: PrimInt> From<Interval<T>> for IntervalSet<T> {
+    fn from(iv: Interval<T>) -> Self {
+        IntervalSet {
+            intervals: vec![iv],
+        }
+    }
+}
+
+#[cfg(test)]
+fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+    intervals
+        .iter()
+        .map(|&iv| Interval::try_from(iv).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_interval() {
+    let a = Interval::new(2u32, 6);
+    assert_eq!((a.lo(), a.hi()), (2, 6));
+    assert_eq!(a.len(), Some(5));
+    assert_eq!(Interval::try_new(6, 2u32), None);
+    assert!(Interval::try_from((6, 2u32)).is_err());
+    assert_eq!(Interval::new(i8::MIN, i8::MAX).len(), None);
+    assert_eq!(Interval::new(-100i8, 26).len(), Some(127));
+    assert!(a.contains(2) && a.contains(6) && !a.contains(7));
+    assert!(a.contains_interval(&Interval::new(3, 6)));
+    assert!(!a.contains_interval(&Interval::new(3, 7)));
+    assert!(a.overlaps(&Interval::new(6, 9)));
+    assert!(!a.overlaps(&Interval::new(7, 9)));
+    assert_eq!(
+        a.intersection(&Interval::new(4, 9)),
+        Some(Interval::new(4, 6))
+    );
+    assert_eq!(a.intersection(&Interval::new(0, 1)), None);
+}
+
+#[test]
+fn test_merge() {
+    assert!(set(&[]).is_empty());
+    assert_eq!(set(&[(5, 8), (0, 2), (3, 3)]), set(&[(0, 3), (5, 8)]));
+    assert_eq!(set(&[(0, 10), (2, 3), (9, 12)]), set(&[(0, 12)]));
+    assert_eq!(set(&[(5, 8), (0, 2), (3, 3)]).len(), Some(8));
+    let mut s = set(&[(0, 1), (5, 6), (10, 12)]);
+    s.insert(Interval::new(3, 4));
+    assert_eq!(s, set(&[(0, 1), (3, 6), (10, 12)]));
+    s.insert(Interval::new(2, 9));
+    assert_eq!(s, set(&[(0, 12)]));
+    s.insert(Interval::new(i32::MAX - 1, i32::MAX));
+    assert_eq!(s.len(), Some(15));
+    s.insert(Interval::new(i32::MIN, -1));
+    assert_eq!(s.len(), None);
+}
+
+#[test]
+fn test_queries() {
+    let s = set(&[(-5, -2), (3, 4), (8, 10)]);
+    assert!(!s.contains(-6));
+    assert!(s.contains(-5));
+    assert!(s.contains(-2));
+    assert!(!s.contains(0));
+    assert!(s.contains(4));
+    assert!(!s.contains(5));
+    assert!(s.contains_interval(&Interval::new(8, 9)));
+    assert!(!s.contains_interval(&Interval::new(3, 8)));
+    assert!(s.overlaps(&Interval::new(5, 8)));
+    assert!(!s.overlaps(&Interval::new(5, 7)));
+    assert_eq!(
+        s.gaps().collect::<Vec<_>>(),
+        vec![Interval::new(-1, 2), Interval::new(5, 7)]
+    );
+}
+
+#[test]
+fn test_set_ops() {
+    let a = set(&[(0, 5), (10, 15)]);
+    let b = set(&[(3, 11), (14, 20)]);
+    assert_eq!(a.union(&b), set(&[(0, 20)]));
+    assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 11), (14, 15)]));
+    assert_eq!(a.difference(&b), set(&[(0, 2), (12, 13)]));
+    assert_eq!(b.difference(&a), set(&[(6, 9), (16, 20)]));
+    assert_eq!(a.difference(&set(&[(-3, 30)])), set(&[]));
+    assert_eq!(
+        a.difference(&set(&[(1, 1), (3, 3)])),
+        set(&[(0, 0), (2, 2), (4, 5), (10, 15)])
+    );
+}
@@ -1,3 +1,6 @@
+pub mod interval;
+pub mod point;
+
 #[macro_use]
 pub mod parser {
     pub use color_eyre::eyre::eyre;
@@ -0,0 +1,247 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! N-dimensional integer points.
+
+use num_traits::{PrimInt, Signed};
+use std::cmp;
+use std::ops;
+
+/// Point with `N` integer coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<T, const N: usize>(pub [T; N]);
+
+pub type Point2<T> = Point<T, 2>;
+pub type Point3<T> = Point<T, 3>;
+
+impl<T: PrimInt + Signed, const N: usize> Point<T, N> {
+    pub fn new(coords: [T; N]) -> Point<T, N> {
+        Point(coords)
+    }
+    pub fn zero() -> Point<T, N> {
+        Point([T::zero(); N])
+    }
+    /// Unit vector along `axis`
+    pub fn unit(axis: usize) -> Point<T, N> {
+        let mut p = Point::zero();
+        p.0[axis] = T::one();
+        p
+    }
+    pub fn manhattan(&self, other: &Point<T, N>) -> T {
+        (0..N).fold(T::zero(), |acc, i| acc + (self.0[i] - other.0[i]).abs())
+    }
+    pub fn chebyshev(&self, other: &Point<T, N>) -> T {
+        (0..N).fold(T::zero(), |acc, i| {
+            cmp::max(acc, (self.0[i] - other.0[i]).abs())
+        })
+    }
+    /// The `2N` orthogonal unit directions: all positive ones first,
+    /// then all negative ones
+    pub fn iter_dirs() -> impl Iterator<Item = Point<T, N>> {
+        (0..N)
+            .map(Point::unit)
+            .chain((0..N).map(|axis| -Point::unit(axis)))
+    }
+    /// The `3^N - 1` directions that include diagonals
+    pub fn iter_dirs_diag() -> impl Iterator<Item = Point<T, N>> {
+        let total = 3_usize.pow(N as u32);
+        (0..total).filter_map(|mut i| {
+            let mut p = Point::zero();
+            for c in p.0.iter_mut() {
+                *c = T::from(i % 3).unwrap() - T::one();
+                i /= 3;
+            }
+            (p != Point::zero()).then_some(p)
+        })
+    }
+    /// Orthogonal neighbours, in the order of [`Point::iter_dirs`]
+    pub fn iter_neighs(&self) -> impl Iterator<Item = Point<T, N>> {
+        let p = *self;
+        Point::iter_dirs().map(move |d| p + d)
+    }
+    /// Neighbours including diagonals, in the order of
+    /// [`Point::iter_dirs_diag`]
+    pub fn iter_neighs_diag(&self) -> impl Iterator<Item = Point<T, N>> {
+        let p = *self;
+        Point::iter_dirs_diag().map(move |d| p + d)
+    }
+    /// Checks if the point is inside the inclusive box `(min, max)`
+    pub fn is_inside(&self, bbox: &(Point<T, N>, Point<T, N>)) -> bool {
+        (0..N).all(|i| bbox.0 .0[i] <= self.0[i] && self.0[i] <= bbox.1 .0[i])
+    }
+}
+
+// Named coordinates only exist in the dimensions that have them, so that
+// `z()` on a 2D point doesn't compile
+
+impl<T: Copy> Point<T, 2> {
+    pub fn x(&self) -> T {
+        self.0[0]
+    }
+    pub fn y(&self) -> T {
+        self.0[1]
+    }
+}
+
+impl<T: Copy> Point<T, 3> {
+    pub fn x(&self) -> T {
+        self.0[0]
+    }
+    pub fn y(&self) -> T {
+        self.0[1]
+    }
+    pub fn z(&self) -> T {
+        self.0[2]
+    }
+}
+
+/// Smallest inclusive box `(min, max)` that contains all the points
+pub fn bounding_box<'a, T, const N: usize>(
+    points: impl IntoIterator<Item = &'a Point<T, N>>,
+) -> Option<(Point<T, N>, Point<T, N>)>
+where
+    T: PrimInt + Signed + 'a,
+{
+    points.into_iter().fold(None, |bbox, p| {
+        let Some((mut min, mut max)) = bbox else {
+            return Some((*p, *p));
+        };
+        for i in 0..N {
+            min.0[i] = cmp::min(min.0[i], p.0[i]);
+            max.0[i] = cmp::max(max.0[i], p.0[i]);
+        }
+        Some((min, max))
+    })
+}
+
+impl<T: PrimInt + Signed, const N: usize> Default for Point<T, N> {
+    fn default() -> Self {
+        Point::zero()
+    }
+}
+
+impl<T, const N: usize> ops::Index<usize> for Point<T, N> {
+    type Output = T;
+    fn index(&self, i: usize) -> &T {
+        &self.0[i]
+    }
+}
+
+impl<T, const N: usize> ops::IndexMut<usize> for Point<T, N> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        &mut self.0[i]
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::Add for Point<T, N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Point(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::AddAssign for Point<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::Sub for Point<T, N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::SubAssign for Point<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::Mul<T> for Point<T, N> {
+    type Output = Self;
+    fn mul(self, rhs: T) -> Self::Output {
+        Point(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<T: PrimInt + Signed, const N: usize> ops::Neg for Point<T, N> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Point(self.0.map(|c| -c))
+    }
+}
+
+impl<T> From<(T, T)> for Point<T, 2> {
+    fn from((x, y): (T, T)) -> Self {
+        Point([x, y])
+    }
+}
+
+impl<T> From<(T, T, T)> for Point<T, 3> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point([x, y, z])
+    }
+}
+
+#[test]
+fn test_ops() {
+    let a = Point::new([1, 2, 3]);
+    let b = Point::new([-1, 5, 0]);
+    assert_eq!(a + b, Point::new([0, 7, 3]));
+    assert_eq!(a - b, Point::new([2, -3, 3]));
+    assert_eq!(a * 3, Point::new([3, 6, 9]));
+    assert_eq!(-a, Point::new([-1, -2, -3]));
+    let mut c = a;
+    c += b;
+    c -= a;
+    assert_eq!(c, b);
+    assert_eq!((a.x(), a.y(), a.z()), (1, 2, 3));
+    let p = Point2::from((7, 8));
+    assert_eq!((p.x(), p.y()), (7, 8));
+    assert_eq!(a[1], 2);
+    assert_eq!(Point2::from((4i64, -4)), Point::new([4, -4]));
+}
+
+#[test]
+fn test_distances() {
+    let a = Point2::<i64>::new([0, 0]);
+    let b = Point2::<i64>::new([3, -4]);
+    assert_eq!(a.manhattan(&b), 7);
+    assert_eq!(a.chebyshev(&b), 4);
+}
+
+#[test]
+fn test_neighs() {
+    let p = Point3::<i32>::new([0, 0, 0]);
+    assert_eq!(
+        p.iter_neighs().collect::<Vec<_>>(),
+        vec![
+            Point::new([1, 0, 0]),
+            Point::new([0, 1, 0]),
+            Point::new([0, 0, 1]),
+            Point::new([-1, 0, 0]),
+            Point::new([0, -1, 0]),
+            Point::new([0, 0, -1]),
+        ]
+    );
+    assert_eq!(p.iter_neighs_diag().count(), 26);
+    assert!(p.iter_neighs_diag().all(|n| p.chebyshev(&n) == 1));
+    assert_eq!(Point2::<i8>::default().iter_neighs_diag().count(), 8);
+}
+
+#[test]
+fn test_bounding_box() {
+    let points = [
+        Point2::<i32>::new([1, 5]),
+        Point2::new([-3, 2]),
+        Point2::new([4, -1]),
+    ];
+    let bbox = bounding_box(&points).unwrap();
+    assert_eq!(bbox, (Point::new([-3, -1]), Point::new([4, 5])));
+    assert!(points.iter().all(|p| p.is_inside(&bbox)));
+    assert!(!Point::new([5, 0]).is_inside(&bbox));
+    assert_eq!(bounding_box::<i32, 2>(&[]), None);
+}
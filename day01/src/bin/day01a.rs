@@ -2,20 +2,18 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
+use eyre::eyre;
 use eyre::Result;
 use std::io::{stdin, BufRead};
 
 use day01::*;
 
 fn process(bufin: impl BufRead) -> Result<Calories> {
-    let input = parser::parse(bufin)?;
-    let top = itertools::max(
-        input
-            .into_iter()
-            .map(|elf| elf.into_iter().sum::<Calories>()),
-    )
-    .unwrap();
-    Ok(top)
+    let mut top = None;
+    for total in elf_totals(bufin) {
+        top = top.max(Some(total?));
+    }
+    top.ok_or_else(|| eyre!("no elves in input"))
 }
 
 #[test]
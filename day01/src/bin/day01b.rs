@@ -2,29 +2,35 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
+use eyre::eyre;
 use eyre::Result;
-use std::collections::BinaryHeap;
+use std::env;
 use std::io::{stdin, BufRead};
 
 use day01::*;
 
-fn process(bufin: impl BufRead) -> Result<Calories> {
-    let input = parser::parse(bufin)?;
-    let mut perelf = input
-        .into_iter()
-        .map(|elf| elf.into_iter().sum::<Calories>())
-        .collect::<BinaryHeap<Calories>>();
-    // BinaryHeap's iter is random, and into_iter_sorted is only in nightly atm
-    Ok(perelf.pop().unwrap() + perelf.pop().unwrap() + perelf.pop().unwrap())
+fn process(bufin: impl BufRead, k: usize) -> Result<Calories> {
+    let mut top = TopK::new(k);
+    for total in elf_totals(bufin) {
+        top.push(total?);
+    }
+    if top.len() < k {
+        return Err(eyre!("only {} elves in input, {} needed", top.len(), k));
+    }
+    top.sum()
 }
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(process(EXAMPLE.as_bytes())?, 45000);
+    assert_eq!(process(EXAMPLE.as_bytes(), 3)?, 45000);
     Ok(())
 }
 
 fn main() -> Result<()> {
-    println!("{}", process(stdin().lock())?);
+    let k = match env::args().nth(1) {
+        Some(arg) => arg.parse()?,
+        None => 3,
+    };
+    println!("{}", process(stdin().lock(), k)?);
     Ok(())
 }
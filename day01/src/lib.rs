@@ -2,8 +2,12 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
-#[cfg(test)]
+use eyre::eyre;
 use eyre::Result;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::BufRead;
+use std::io::Lines;
 
 pub type Calories = u32;
 
@@ -23,48 +27,122 @@ pub const EXAMPLE: &str = "1000
 10000
 ";
 
-pub mod parser {
-    use eyre::eyre;
-    use eyre::Result;
-    use nom::character::complete as character;
-    use nom::character::complete::newline;
-    use nom::combinator;
-    use nom::multi;
-    use nom::IResult;
-    use std::io::BufRead;
-
-    use super::*;
-
-    fn calories(input: &str) -> IResult<&str, Calories> {
-        let (input, calories) = character::u32(input)?;
-        let (input, _) = newline(input)?;
-        Ok((input, calories))
-    }
+/// Iterator over the total calories of each elf, reading the input one
+/// line at a time
+pub struct ElfTotals<B> {
+    lines: Lines<B>,
+    lineno: usize,
+}
 
-    fn elf(input: &str) -> IResult<&str, Vec<Calories>> {
-        let (input, elf) = multi::many1(calories)(input)?;
-        Ok((input, elf))
+pub fn elf_totals<B: BufRead>(bufin: B) -> ElfTotals<B> {
+    ElfTotals {
+        lines: bufin.lines(),
+        lineno: 0,
     }
+}
 
-    pub fn parse(mut bufin: impl BufRead) -> Result<Vec<Vec<Calories>>> {
-        let mut input = String::default();
-        bufin.read_to_string(&mut input)?;
-        let result = combinator::all_consuming(multi::separated_list1(newline, elf))(&input);
-        Ok(result.map_err(|e| eyre!("error reading input: {:?}", e))?.1)
+impl<B: BufRead> Iterator for ElfTotals<B> {
+    type Item = Result<Calories>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut total: Option<Calories> = None;
+        for line in self.lines.by_ref() {
+            self.lineno += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if line.is_empty() {
+                return Some(total.ok_or_else(|| eyre!("line {}: elf without items", self.lineno)));
+            }
+            let calories = match line.parse::<Calories>() {
+                Ok(calories) => calories,
+                Err(e) => return Some(Err(eyre!("line {}: {}", self.lineno, e))),
+            };
+            match total.unwrap_or(0).checked_add(calories) {
+                Some(sum) => total = Some(sum),
+                None => return Some(Err(eyre!("line {}: calories overflow", self.lineno))),
+            }
+        }
+        total.map(Ok)
     }
 }
 
 #[test]
-fn test() -> Result<()> {
+fn test_elf_totals() -> Result<()> {
     assert_eq!(
-        parser::parse(EXAMPLE.as_bytes())?,
-        &[
-            vec![1000, 2000, 3000],
-            vec![4000],
-            vec![5000, 6000],
-            vec![7000, 8000, 9000],
-            vec![10000],
-        ]
+        elf_totals(EXAMPLE.as_bytes()).collect::<Result<Vec<_>>>()?,
+        vec![6000, 4000, 11000, 24000, 10000]
     );
+    assert!(elf_totals("1\n\n\n2\n".as_bytes())
+        .collect::<Result<Vec<_>>>()
+        .is_err());
+    assert!(elf_totals("4294967295\n1\n".as_bytes())
+        .collect::<Result<Vec<_>>>()
+        .is_err());
+    Ok(())
+}
+
+/// Keeps the `k` largest values pushed, in constant memory
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<Calories>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> TopK {
+        TopK {
+            k,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, calories: Calories) {
+        self.heap.push(Reverse(calories));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// The values kept, largest first
+    pub fn values(&self) -> Vec<Calories> {
+        let mut values = self.heap.iter().map(|r| r.0).collect::<Vec<_>>();
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        values
+    }
+
+    pub fn sum(&self) -> Result<Calories> {
+        self.heap
+            .iter()
+            .try_fold(0 as Calories, |acc, r| acc.checked_add(r.0))
+            .ok_or_else(|| eyre!("calories overflow"))
+    }
+}
+
+#[test]
+fn test_topk() -> Result<()> {
+    let mut top = TopK::new(3);
+    for total in elf_totals(EXAMPLE.as_bytes()) {
+        top.push(total?);
+    }
+    assert_eq!(top.values(), vec![24000, 11000, 10000]);
+    assert_eq!(top.sum()?, 45000);
+    let mut top = TopK::new(2);
+    top.push(Calories::MAX);
+    top.push(1);
+    assert!(top.sum().is_err());
+    // Huge values of k don't allocate upfront
+    let mut top = TopK::new(usize::MAX);
+    top.push(1);
+    assert_eq!(top.values(), vec![1]);
     Ok(())
 }
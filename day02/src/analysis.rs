@@ -0,0 +1,237 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Strategy guide analysis: scores of every possible meaning of the
+//! strategy column, under both interpretations.
+
+use eyre::eyre;
+use eyre::Result;
+use std::fmt;
+
+use super::game::*;
+
+/// Meaning of each strategy letter: a [`Move`] in [`Mode::Move`], or an
+/// index in [`ALLOUTCOMES`] in [`Mode::Outcome`]
+pub type Mapping = Vec<usize>;
+
+/// All the ways of giving `letters` letters distinct meanings out of
+/// `meanings`, in lexicographic order, so that the puzzle's one is first
+pub fn mappings(meanings: usize, letters: usize) -> Vec<Mapping> {
+    fn extend(meanings: usize, letters: usize, prefix: &mut Mapping, all: &mut Vec<Mapping>) {
+        if prefix.len() == letters {
+            all.push(prefix.clone());
+            return;
+        }
+        for meaning in 0..meanings {
+            if !prefix.contains(&meaning) {
+                prefix.push(meaning);
+                extend(meanings, letters, prefix, all);
+                prefix.pop();
+            }
+        }
+    }
+    let mut all = vec![];
+    extend(meanings, letters, &mut vec![], &mut all);
+    all
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundScore {
+    pub round: Round,
+    pub mine: Move,
+    pub shape: u32,
+    pub outcome: u32,
+}
+
+impl RoundScore {
+    pub fn new(game: &Game, round: Round, mapping: &Mapping, mode: Mode) -> Result<RoundScore> {
+        let meaning = *mapping
+            .get(round.column)
+            .ok_or_else(|| eyre!("no meaning for strategy column {}", round.column))?;
+        // The meaning stands in for the column, so the game checks it
+        let mine = game
+            .my_move(
+                &Round {
+                    theirs: round.theirs,
+                    column: meaning,
+                },
+                mode,
+            )
+            .map_err(|e| eyre!("mapping {:?}: {}", mapping, e))?;
+        Ok(RoundScore {
+            round,
+            mine,
+            shape: game.scoring.shape[mine],
+            outcome: game.scoring.outcome[game.outcome(mine, round.theirs) as usize],
+        })
+    }
+
+    pub fn total(&self) -> u32 {
+        self.shape + self.outcome
+    }
+}
+
+/// Per-round scores of a strategy guide with the given meaning of its
+/// letters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub game: Game,
+    pub mapping: Mapping,
+    pub mode: Mode,
+    pub rounds: Vec<RoundScore>,
+}
+
+impl Breakdown {
+    pub fn new(game: &Game, rounds: &[Round], mapping: Mapping, mode: Mode) -> Result<Breakdown> {
+        let rounds = rounds
+            .iter()
+            .map(|round| RoundScore::new(game, *round, &mapping, mode))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Breakdown {
+            game: game.clone(),
+            mapping,
+            mode,
+            rounds,
+        })
+    }
+
+    pub fn total(&self) -> u32 {
+        self.rounds.iter().map(RoundScore::total).sum()
+    }
+
+    /// Meaning of the letters, as in "X=Rock Y=Paper Z=Scissors"
+    pub fn mapping_name(&self) -> String {
+        const OUTCOMES: [&str; 3] = ["lose", "draw", "win"];
+        self.game
+            .strategy
+            .iter()
+            .zip(&self.mapping)
+            .map(|(letter, meaning)| match self.mode {
+                Mode::Move => format!("{}={}", letter, self.game.names[*meaning]),
+                Mode::Outcome => format!("{}={}", letter, OUTCOMES[*meaning]),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Table with one line per round
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:<8}  {:<5}  {:<8}  {:>5}  {:>7}  {:>5}",
+            "Round", "Them", "Strat", "Me", "Shape", "Outcome", "Total"
+        )?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5}  {:<8}  {:<5}  {:<8}  {:>5}  {:>7}  {:>5}",
+                i + 1,
+                self.game.names[round.round.theirs],
+                self.game.strategy[round.round.column],
+                self.game.names[round.mine],
+                round.shape,
+                round.outcome,
+                round.total()
+            )?;
+        }
+        let shape = self.rounds.iter().map(|r| r.shape).sum::<u32>();
+        let outcome = self.rounds.iter().map(|r| r.outcome).sum::<u32>();
+        writeln!(
+            f,
+            "{:>5}  {:<8}  {:<5}  {:<8}  {:>5}  {:>7}  {:>5}",
+            "Sum",
+            "",
+            "",
+            "",
+            shape,
+            outcome,
+            self.total()
+        )
+    }
+}
+
+/// Breakdowns of `rounds` for every mapping of the game's strategy
+/// letters under both interpretations
+pub fn analyze(game: &Game, rounds: &[Round]) -> Result<Vec<Breakdown>> {
+    [(Mode::Move, game.moves), (Mode::Outcome, ALLOUTCOMES.len())]
+        .into_iter()
+        .flat_map(|(mode, meanings)| {
+            mappings(meanings, game.strategy.len())
+                .into_iter()
+                .map(move |mapping| Breakdown::new(game, rounds, mapping, mode))
+        })
+        .collect()
+}
+
+/// Breakdown with the highest total; ties go to the first one
+pub fn best(breakdowns: &[Breakdown]) -> Option<&Breakdown> {
+    breakdowns
+        .iter()
+        .rev()
+        .max_by_key(|breakdown| breakdown.total())
+}
+
+/// Breakdown with the lowest total; ties go to the first one
+pub fn worst(breakdowns: &[Breakdown]) -> Option<&Breakdown> {
+    breakdowns.iter().min_by_key(|breakdown| breakdown.total())
+}
+
+#[test]
+fn test_invalid() {
+    let game = Game::rps();
+    let round = Round {
+        theirs: 0,
+        column: 1,
+    };
+    assert!(RoundScore::new(&game, round, &vec![0, 3, 1], Mode::Move).is_err());
+    assert!(RoundScore::new(&game, round, &vec![0, 3, 1], Mode::Outcome).is_err());
+    assert!(RoundScore::new(&game, round, &vec![0], Mode::Move).is_err());
+    let round = Round {
+        theirs: 3,
+        column: 1,
+    };
+    assert!(RoundScore::new(&game, round, &vec![0, 1, 2], Mode::Move).is_err());
+}
+
+#[test]
+fn test_mappings() {
+    assert_eq!(mappings(3, 3).len(), 6);
+    assert_eq!(mappings(3, 3)[0], vec![0, 1, 2]);
+    assert_eq!(mappings(5, 3).len(), 60);
+    assert!(mappings(3, 4).is_empty());
+}
+
+#[test]
+fn test() -> Result<()> {
+    let game = Game::rps();
+    let rounds = game.parse(super::EXAMPLE.as_bytes())?;
+    let breakdowns = analyze(&game, &rounds)?;
+    assert_eq!(breakdowns.len(), 12);
+    assert_eq!(breakdowns[0].total(), 15);
+    assert_eq!(breakdowns[6].total(), 12);
+    assert_eq!(breakdowns[0].mapping_name(), "X=Rock Y=Paper Z=Scissors");
+    assert_eq!(breakdowns[6].mapping_name(), "X=lose Y=draw Z=win");
+    let best = best(&breakdowns).ok_or_else(|| eyre!("no best"))?;
+    assert_eq!(
+        (best.mode, best.mapping_name(), best.total()),
+        (Mode::Move, "X=Scissors Y=Paper Z=Rock".to_string(), 24)
+    );
+    let worst = worst(&breakdowns).ok_or_else(|| eyre!("no worst"))?;
+    assert_eq!(
+        (worst.mode, worst.mapping_name(), worst.total()),
+        (Mode::Move, "X=Rock Y=Scissors Z=Paper".to_string(), 6)
+    );
+    assert_eq!(
+        breakdowns[0].to_string(),
+        "Round  Them      Strat  Me        Shape  Outcome  Total
+    1  Rock      Y      Paper         2        6      8
+    2  Paper     X      Rock          1        0      1
+    3  Scissors  Z      Scissors      3        3      6
+  Sum                                 6        9     15
+"
+    );
+    Ok(())
+}
@@ -0,0 +1,43 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use eyre::eyre;
+use eyre::Result;
+use std::io::{stdin, BufRead};
+
+use day02::analysis::*;
+use day02::game::*;
+
+fn process(bufin: impl BufRead) -> Result<Vec<Breakdown>> {
+    let game = Game::rps();
+    let rounds = game.parse(bufin)?;
+    analyze(&game, &rounds)
+}
+
+#[test]
+fn test() -> Result<()> {
+    let breakdowns = process(day02::EXAMPLE.as_bytes())?;
+    assert_eq!(breakdowns.len(), 12);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let breakdowns = process(stdin().lock())?;
+    for breakdown in &breakdowns {
+        println!(
+            "{:?} {}: {}",
+            breakdown.mode,
+            breakdown.mapping_name(),
+            breakdown.total()
+        );
+    }
+    let best = best(&breakdowns).ok_or_else(|| eyre!("empty analysis"))?;
+    let worst = worst(&breakdowns).ok_or_else(|| eyre!("empty analysis"))?;
+    println!();
+    println!("Best: {:?} {}\n", best.mode, best.mapping_name());
+    println!("{}", best);
+    println!("Worst: {:?} {}\n", worst.mode, worst.mapping_name());
+    print!("{}", worst);
+    Ok(())
+}
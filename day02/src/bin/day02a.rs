@@ -5,30 +5,17 @@
 use eyre::Result;
 use std::io::{stdin, BufRead};
 
-use day02::*;
-
-fn strat2play(strat: Strat) -> Play {
-    match strat {
-        Strat::X => Play::Rock,
-        Strat::Y => Play::Paper,
-        Strat::Z => Play::Scissors,
-    }
-}
-
-fn round_score(round: &(Play, Strat)) -> u32 {
-    let p1 = round.0;
-    let p2 = strat2play(round.1);
-    p2.score() + round_beat_score(p2, p1)
-}
+use day02::game::*;
 
 fn process(bufin: impl BufRead) -> Result<u32> {
-    let input = parser::parse(bufin)?;
-    Ok(input.iter().map(round_score).sum())
+    let game = Game::rps();
+    let rounds = game.parse(bufin)?;
+    game.score(&rounds, Mode::Move)
 }
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(process(EXAMPLE.as_bytes())?, 15);
+    assert_eq!(process(day02::EXAMPLE.as_bytes())?, 15);
     Ok(())
 }
 
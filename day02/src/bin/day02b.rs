@@ -3,43 +3,19 @@
 // file 'LICENSE', which is part of this source code package.
 
 use eyre::Result;
-use num_traits::cast::FromPrimitive;
 use std::io::{stdin, BufRead};
 
-use day02::*;
-
-pub fn myplay_calc(strat: Strat, hisplay: Play) -> Play {
-    let hisval = hisplay as u32;
-    Play::from_u32(match strat {
-        Strat::X => (hisval + 2) % 3, // lose
-        Strat::Y => hisval,           // draw
-        Strat::Z => (hisval + 1) % 3, // win
-    })
-    .unwrap()
-}
-
-#[test]
-fn test_myplay_calc() {
-    assert_eq!(myplay_calc(Strat::Y, Play::Rock), Play::Rock);
-    assert_eq!(myplay_calc(Strat::X, Play::Paper), Play::Rock);
-    assert_eq!(myplay_calc(Strat::Z, Play::Scissors), Play::Rock);
-}
-
-fn eval(entry: (Play, Strat)) -> u32 {
-    let hisplay = entry.0;
-    let strat = entry.1;
-    let myplay = myplay_calc(strat, hisplay);
-    myplay.score() + round_beat_score(myplay, hisplay)
-}
+use day02::game::*;
 
 fn process(bufin: impl BufRead) -> Result<u32> {
-    let input = parser::parse(bufin)?;
-    Ok(input.into_iter().map(eval).sum())
+    let game = Game::rps();
+    let rounds = game.parse(bufin)?;
+    game.score(&rounds, Mode::Outcome)
 }
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(process(EXAMPLE.as_bytes())?, 12);
+    assert_eq!(process(day02::EXAMPLE.as_bytes())?, 12);
     Ok(())
 }
 
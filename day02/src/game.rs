@@ -0,0 +1,287 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Generic cyclic game: any odd number of moves, where each move beats
+//! the half of the others that come right before it.
+
+use eyre::eyre;
+use eyre::Result;
+use std::io::BufRead;
+
+/// Index of a move; for rock-paper-scissors, 0 is rock, 1 paper and
+/// 2 scissors
+pub type Move = usize;
+
+/// Outcome of a round, from our point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+pub const ALLOUTCOMES: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+/// How the strategy column is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The column is the move we play, as in part a
+    Move,
+    /// The column is the outcome we want (lose, draw, win), as in part b
+    Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoring {
+    /// Score of each move we play
+    pub shape: Vec<u32>,
+    /// Score of each outcome, indexed by [`Outcome`]
+    pub outcome: [u32; 3],
+}
+
+impl Scoring {
+    /// The puzzle's scoring: 1 for the first move, 2 for the second, and
+    /// so on; 0 for losing, 3 for a draw and 6 for a win
+    pub fn puzzle(moves: usize) -> Scoring {
+        Scoring {
+            shape: (1..=moves as u32).collect(),
+            outcome: [0, 3, 6],
+        }
+    }
+}
+
+/// Round as read from the strategy guide: the opponent's move and the
+/// index of the letter in the strategy column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Round {
+    pub theirs: Move,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub moves: usize,
+    /// Letter of each move in the opponent column
+    pub opponent: Vec<char>,
+    /// Letters of the strategy column
+    ///
+    /// In [`Mode::Move`] they are the moves, in order; in
+    /// [`Mode::Outcome`] the first three are lose, draw and win.
+    pub strategy: Vec<char>,
+    pub scoring: Scoring,
+    /// Name of each move, for reports; the opponent letters by default
+    pub names: Vec<String>,
+}
+
+impl Game {
+    /// Creates a game with `moves` moves and the puzzle's scoring
+    ///
+    /// There must be at least 3 moves, so that every outcome is possible.
+    pub fn new(moves: usize, opponent: &str, strategy: &str) -> Result<Game> {
+        if moves < 3 || moves.is_multiple_of(2) {
+            return Err(eyre!(
+                "cyclic games need an odd number of moves of at least 3, not {}",
+                moves
+            ));
+        }
+        let opponent = opponent.chars().collect::<Vec<_>>();
+        let strategy = strategy.chars().collect::<Vec<_>>();
+        if opponent.len() != moves {
+            return Err(eyre!(
+                "expected {} opponent letters, got {:?}",
+                moves,
+                opponent
+            ));
+        }
+        for letters in [&opponent, &strategy] {
+            if (1..letters.len()).any(|i| letters[..i].contains(&letters[i])) {
+                return Err(eyre!("repeated letter in {:?}", letters));
+            }
+        }
+        Ok(Game {
+            moves,
+            names: opponent.iter().map(char::to_string).collect(),
+            opponent,
+            strategy,
+            scoring: Scoring::puzzle(moves),
+        })
+    }
+
+    /// Rock-paper-scissors with the puzzle's letters
+    pub fn rps() -> Game {
+        Game::new(3, "ABC", "XYZ")
+            .and_then(|game| game.with_names(&["Rock", "Paper", "Scissors"]))
+            .expect("valid game")
+    }
+
+    pub fn with_names(mut self, names: &[&str]) -> Result<Game> {
+        if names.len() != self.moves {
+            return Err(eyre!(
+                "expected {} move names, got {}",
+                self.moves,
+                names.len()
+            ));
+        }
+        self.names = names.iter().map(|name| name.to_string()).collect();
+        Ok(self)
+    }
+
+    pub fn with_scoring(mut self, scoring: Scoring) -> Result<Game> {
+        if scoring.shape.len() != self.moves {
+            return Err(eyre!(
+                "expected {} shape scores, got {}",
+                self.moves,
+                scoring.shape.len()
+            ));
+        }
+        self.scoring = scoring;
+        Ok(self)
+    }
+
+    /// Outcome of playing `mine` against `theirs`
+    pub fn outcome(&self, mine: Move, theirs: Move) -> Outcome {
+        let diff = (mine + self.moves - theirs) % self.moves;
+        if diff == 0 {
+            Outcome::Draw
+        } else if diff <= self.moves / 2 {
+            Outcome::Win
+        } else {
+            Outcome::Lose
+        }
+    }
+
+    /// Move that gets us `outcome` against `theirs`
+    ///
+    /// When there is more than one, we pick the closest to `theirs`.
+    pub fn play_for(&self, theirs: Move, outcome: Outcome) -> Move {
+        match outcome {
+            Outcome::Lose => (theirs + self.moves - 1) % self.moves,
+            Outcome::Draw => theirs,
+            Outcome::Win => (theirs + 1) % self.moves,
+        }
+    }
+
+    pub fn round_score(&self, mine: Move, theirs: Move) -> u32 {
+        self.scoring.shape[mine] + self.scoring.outcome[self.outcome(mine, theirs) as usize]
+    }
+
+    /// Move we play in `round` when interpreting it with `mode`
+    pub fn my_move(&self, round: &Round, mode: Mode) -> Result<Move> {
+        if round.theirs >= self.moves {
+            return Err(eyre!(
+                "opponent move {} is invalid in a game of {} moves",
+                round.theirs,
+                self.moves
+            ));
+        }
+        match mode {
+            Mode::Move if round.column < self.moves => Ok(round.column),
+            Mode::Outcome if round.column < ALLOUTCOMES.len() => {
+                Ok(self.play_for(round.theirs, ALLOUTCOMES[round.column]))
+            }
+            _ => Err(eyre!(
+                "strategy column {} has no meaning in {:?} mode",
+                round.column,
+                mode
+            )),
+        }
+    }
+
+    pub fn score(&self, rounds: &[Round], mode: Mode) -> Result<u32> {
+        rounds.iter().try_fold(0, |total, round| {
+            Ok(total + self.round_score(self.my_move(round, mode)?, round.theirs))
+        })
+    }
+
+    /// Parses a strategy guide with this game's letters, skipping empty
+    /// lines
+    pub fn parse(&self, bufin: impl BufRead) -> Result<Vec<Round>> {
+        let letter = |letters: &[char], c: char, lineno: usize| {
+            letters
+                .iter()
+                .position(|l| *l == c)
+                .ok_or_else(|| eyre!("line {}: unexpected letter {:?}", lineno, c))
+        };
+        let mut rounds = vec![];
+        for (i, line) in bufin.lines().enumerate() {
+            let line = line?;
+            let lineno = i + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let (Some(theirs), Some(column), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(eyre!(
+                    "line {}: expected two letters, got {:?}",
+                    lineno,
+                    line
+                ));
+            };
+            let single = |field: &str| {
+                let mut chars = field.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(eyre!("line {}: expected a letter, got {:?}", lineno, field)),
+                }
+            };
+            rounds.push(Round {
+                theirs: letter(&self.opponent, single(theirs)?, lineno)?,
+                column: letter(&self.strategy, single(column)?, lineno)?,
+            });
+        }
+        Ok(rounds)
+    }
+}
+
+#[test]
+fn test_rps() -> Result<()> {
+    let game = Game::rps();
+    // Paper beats rock, rock beats scissors, scissors beat paper
+    for (mine, theirs) in [(1, 0), (0, 2), (2, 1)] {
+        assert_eq!(game.outcome(mine, theirs), Outcome::Win);
+        assert_eq!(game.outcome(theirs, mine), Outcome::Lose);
+        assert_eq!(game.round_score(mine, theirs), mine as u32 + 7);
+    }
+    assert_eq!(game.play_for(0, Outcome::Draw), 0);
+    assert_eq!(game.play_for(1, Outcome::Lose), 0);
+    assert_eq!(game.play_for(2, Outcome::Win), 0);
+    let rounds = game.parse(super::EXAMPLE.as_bytes())?;
+    assert_eq!(game.score(&rounds, Mode::Move)?, 15);
+    assert_eq!(game.score(&rounds, Mode::Outcome)?, 12);
+    assert!(game.parse("A W\n".as_bytes()).is_err());
+    assert!(game.parse("A\n".as_bytes()).is_err());
+    assert_eq!(game.parse("\nA X\n\nB Y\n\n".as_bytes())?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_cyclic() -> Result<()> {
+    assert!(Game::new(4, "ABCD", "WXYZ").is_err());
+    assert!(Game::new(1, "A", "X").is_err());
+    let game = Game::new(7, "ABCDEFG", "TUVWXYZ")?;
+    for theirs in 0..7 {
+        let outcomes = (0..7).map(|mine| game.outcome(mine, theirs));
+        assert_eq!(outcomes.clone().filter(|o| *o == Outcome::Win).count(), 3);
+        assert_eq!(outcomes.filter(|o| *o == Outcome::Lose).count(), 3);
+        for outcome in ALLOUTCOMES {
+            assert_eq!(
+                game.outcome(game.play_for(theirs, outcome), theirs),
+                outcome
+            );
+        }
+    }
+    // Only the first three letters mean something as outcomes
+    let rounds = game.parse("A Z\n".as_bytes())?;
+    assert_eq!(game.score(&rounds, Mode::Move)?, 7);
+    assert!(game.score(&rounds, Mode::Outcome).is_err());
+    // Rounds built by hand are checked too
+    let game = Game::rps();
+    let round = |theirs, column| Round { theirs, column };
+    assert!(game.score(&[round(0, 5)], Mode::Move).is_err());
+    assert!(game.score(&[round(0, 5)], Mode::Outcome).is_err());
+    assert!(game.score(&[round(3, 0)], Mode::Move).is_err());
+    assert!(game.score(&[round(3, 0)], Mode::Outcome).is_err());
+    Ok(())
+}
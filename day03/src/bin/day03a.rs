@@ -3,28 +3,16 @@
 // file 'LICENSE', which is part of this source code package.
 
 use eyre::Result;
-use std::collections::HashSet;
 use std::io::{stdin, BufRead};
 
 use day03::*;
 
 fn process(bufin: impl BufRead) -> Result<u32> {
     let input = parser::parse(bufin)?;
-    Ok(input
-        .into_iter()
-        .map(|rucksack| {
-            // Compartment size:
-            let csize = rucksack.len() / 2;
-            let c1 = rucksack[0..csize]
-                .iter()
-                .copied()
-                .collect::<HashSet<Item>>();
-            let c2 = rucksack[csize..].iter().copied().collect::<HashSet<Item>>();
-            (c1.intersection(&c2))
-                .map(|item| item.priority() as u32)
-                .sum::<u32>()
-        })
-        .sum())
+    input
+        .iter()
+        .map(|rucksack| Ok(misplaced(rucksack)?.priority() as u32))
+        .sum()
 }
 
 #[test]
@@ -3,27 +3,15 @@
 // file 'LICENSE', which is part of this source code package.
 
 use eyre::Result;
-use std::collections::HashSet;
 use std::io::{stdin, BufRead};
 
 use day03::*;
 
 fn process(bufin: impl BufRead) -> Result<u32> {
     let input = parser::parse(bufin)?;
-    let sets = input
+    Ok(badges(&input, 3)?
         .into_iter()
-        .map(|vec| vec.into_iter().collect::<HashSet<Item>>())
-        .collect::<Vec<_>>();
-    Ok(num::range_step(0, sets.len(), 3)
-        .map(|i| {
-            sets[i]
-                .intersection(&sets[i + 1])
-                .copied()
-                .collect::<HashSet<_>>()
-                .intersection(&sets[i + 2])
-                .map(|item| item.priority() as u32)
-                .sum::<u32>()
-        })
+        .map(|item| item.priority() as u32)
         .sum())
 }
 
@@ -18,6 +18,14 @@ impl Item {
             self.0 as u8 - b'A' + 27
         }
     }
+
+    pub fn from_priority(priority: u8) -> Result<Item> {
+        match priority {
+            1..=26 => Ok(Item((b'a' + priority - 1) as char)),
+            27..=52 => Ok(Item((b'A' + priority - 27) as char)),
+            _ => Err(eyre!("Invalid priority {}", priority)),
+        }
+    }
 }
 
 impl TryFrom<char> for Item {
@@ -33,6 +41,119 @@ impl TryFrom<char> for Item {
 
 pub type Rucksack = Vec<Item>;
 
+/// Set of items, with bit N set for the item with priority N
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn new() -> ItemSet {
+        ItemSet(0)
+    }
+
+    pub fn insert(&mut self, item: Item) {
+        self.0 |= 1 << item.priority();
+    }
+
+    pub fn contains(&self, item: Item) -> bool {
+        self.0 & (1 << item.priority()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    /// Items in the set, by increasing priority
+    pub fn iter(&self) -> impl Iterator<Item = Item> + '_ {
+        (1..=52).filter_map(|p| {
+            if self.0 & (1 << p) != 0 {
+                Item::from_priority(p).ok()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The only item in the set
+    pub fn single(&self) -> Result<Item> {
+        if self.len() == 1 {
+            Item::from_priority(self.0.trailing_zeros() as u8)
+        } else {
+            Err(eyre!(
+                "expected a single item, got {:?}",
+                self.iter().collect::<Vec<_>>()
+            ))
+        }
+    }
+}
+
+impl FromIterator<Item> for ItemSet {
+    fn from_iter<I: IntoIterator<Item = Item>>(iter: I) -> Self {
+        let mut set = ItemSet::new();
+        for item in iter {
+            set.insert(item);
+        }
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Item> for ItemSet {
+    fn from_iter<I: IntoIterator<Item = &'a Item>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+/// Splits the rucksack in its two compartments
+pub fn compartments(rucksack: &[Item]) -> Result<(ItemSet, ItemSet)> {
+    if !rucksack.len().is_multiple_of(2) {
+        return Err(eyre!(
+            "rucksack with odd number of items {}",
+            rucksack.len()
+        ));
+    }
+    let (c1, c2) = rucksack.split_at(rucksack.len() / 2);
+    Ok((c1.iter().collect(), c2.iter().collect()))
+}
+
+/// The item that is in both compartments of the rucksack
+pub fn misplaced(rucksack: &[Item]) -> Result<Item> {
+    let (c1, c2) = compartments(rucksack)?;
+    c1.intersection(&c2).single()
+}
+
+/// The badge of each group of `k` rucksacks: the only item they all have
+pub fn badges(rucksacks: &[Rucksack], k: usize) -> Result<Vec<Item>> {
+    if k == 0 || !rucksacks.len().is_multiple_of(k) {
+        return Err(eyre!(
+            "can't split {} rucksacks in groups of {}",
+            rucksacks.len(),
+            k
+        ));
+    }
+    rucksacks
+        .chunks(k)
+        .map(|group| {
+            group
+                .iter()
+                .map(|rucksack| rucksack.iter().collect::<ItemSet>())
+                .reduce(|a, b| a.intersection(&b))
+                .unwrap_or_default()
+                .single()
+        })
+        .collect()
+}
+
 pub const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
@@ -83,3 +204,22 @@ fn test() -> Result<()> {
     assert_eq!(input[5].len(), 24);
     Ok(())
 }
+
+#[test]
+fn test_itemset() -> Result<()> {
+    let input = parser::parse(EXAMPLE.as_bytes())?;
+    for p in 1..=52 {
+        assert_eq!(Item::from_priority(p)?.priority(), p);
+    }
+    let set = input[0].iter().collect::<ItemSet>();
+    assert_eq!(set.iter().collect::<ItemSet>(), set);
+    assert!(set.contains(Item('v')));
+    assert!(!set.contains(Item('a')));
+    assert_eq!(set.union(&set), set);
+    assert_eq!(misplaced(&input[0])?, Item('p'));
+    assert_eq!(badges(&input, 3)?, vec![Item('r'), Item('Z')]);
+    assert!(badges(&input, 4).is_err());
+    assert!(badges(&input, 1).is_err());
+    assert!(misplaced(&input[0][1..]).is_err());
+    Ok(())
+}
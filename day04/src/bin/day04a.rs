@@ -11,9 +11,7 @@ fn process(bufin: impl BufRead) -> Result<usize> {
     let input = parser::parse(bufin)?;
     Ok(input
         .into_iter()
-        .filter(|((ini1, end1), (ini2, end2))| {
-            ini1 >= ini2 && end1 <= end2 || ini1 <= ini2 && end1 >= end2
-        })
+        .filter(|(a1, a2)| a1.contains_interval(a2) || a2.contains_interval(a1))
         .count())
 }
 
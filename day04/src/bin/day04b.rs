@@ -9,12 +9,7 @@ use day04::*;
 
 fn process(bufin: impl BufRead) -> Result<usize> {
     let input = parser::parse(bufin)?;
-    Ok(input
-        .into_iter()
-        .filter(|((ini1, end1), (ini2, end2))| {
-            ini1 <= ini2 && end1 >= ini2 || ini2 <= ini1 && end2 >= ini1
-        })
-        .count())
+    Ok(input.into_iter().filter(|(a1, a2)| a1.overlaps(a2)).count())
 }
 
 #[test]
@@ -5,6 +5,8 @@
 #[cfg(test)]
 use eyre::Result;
 
+pub use aoc::interval::Interval;
+
 pub const EXAMPLE: &str = "2-4,6-8
 2-3,4-5
 5-7,7-9
@@ -15,7 +17,7 @@ pub const EXAMPLE: &str = "2-4,6-8
 
 pub type Section = u32;
 
-pub type Assignment = (Section, Section);
+pub type Assignment = Interval<Section>;
 
 pub mod parser {
     use eyre::eyre;
@@ -36,8 +38,7 @@ pub mod parser {
     fn assignment(input: &str) -> IResult<&str, Assignment> {
         let (input, min) = section(input)?;
         let (input, _) = character::char('-')(input)?;
-        let (input, max) = section(input)?;
-        Ok((input, (min, max)))
+        combinator::map_opt(section, move |max| Interval::try_new(min, max))(input)
     }
 
     fn assnpair(input: &str) -> IResult<&str, (Assignment, Assignment)> {
@@ -58,16 +59,19 @@ pub mod parser {
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(
-        parser::parse(EXAMPLE.as_bytes())?,
-        &[
-            ((2, 4), (6, 8)),
-            ((2, 3), (4, 5)),
-            ((5, 7), (7, 9)),
-            ((2, 8), (3, 7)),
-            ((6, 6), (4, 6)),
-            ((2, 6), (4, 8)),
-        ]
-    );
+    let assignments = [
+        ((2, 4), (6, 8)),
+        ((2, 3), (4, 5)),
+        ((5, 7), (7, 9)),
+        ((2, 8), (3, 7)),
+        ((6, 6), (4, 6)),
+        ((2, 6), (4, 8)),
+    ]
+    .into_iter()
+    .map(|(a1, a2)| Ok((a1.try_into()?, a2.try_into()?)))
+    .collect::<Result<Vec<_>>>()?;
+    assert_eq!(parser::parse(EXAMPLE.as_bytes())?, assignments);
+    // Sections must not be reversed
+    assert!(parser::parse("4-2,1-1\n".as_bytes()).is_err());
     Ok(())
 }
@@ -7,23 +7,13 @@ use std::io::{stdin, BufRead};
 
 use day05::*;
 
-pub fn do_move(state: &mut State, mv: &Move) {
-    for _ in 0..mv.num {
-        let c = state.0[mv.from].pop_back().unwrap();
-        state.0[mv.to].push_back(c);
-    }
-}
-
 fn process(bufin: impl BufRead) -> Result<String> {
-    let (mut state, moves) = parser::parse(bufin)?;
+    let (state, moves) = parser::parse(bufin)?;
+    let mut crane = Crane::new(Mode::CrateMover9000, state)?;
     for m in &moves {
-        do_move(&mut state, m);
+        crane.apply(m)?;
     }
-    Ok(state
-        .0
-        .into_iter()
-        .flat_map(|mut stack| stack.pop_back())
-        .collect::<String>())
+    Ok(crane.state().tops())
 }
 
 #[test]
@@ -3,30 +3,17 @@
 // file 'LICENSE', which is part of this source code package.
 
 use eyre::Result;
-use std::collections::VecDeque;
 use std::io::{stdin, BufRead};
 
 use day05::*;
 
-pub fn do_move(state: &mut State, mv: &Move) {
-    let mut crane = VecDeque::new();
-    for _ in 0..mv.num {
-        let c = state.0[mv.from].pop_back().unwrap();
-        crane.push_front(c);
-    }
-    state.0[mv.to].append(&mut crane);
-}
-
 fn process(bufin: impl BufRead) -> Result<String> {
-    let (mut state, moves) = parser::parse(bufin)?;
+    let (state, moves) = parser::parse(bufin)?;
+    let mut crane = Crane::new(Mode::CrateMover9001, state)?;
     for m in &moves {
-        do_move(&mut state, m);
+        crane.apply(m)?;
     }
-    Ok(state
-        .0
-        .into_iter()
-        .flat_map(|mut stack| stack.pop_back())
-        .collect::<String>())
+    Ok(crane.state().tops())
 }
 
 #[test]
@@ -3,8 +3,9 @@
 // file 'LICENSE', which is part of this source code package.
 
 use std::collections::VecDeque;
+use std::fmt;
 
-#[cfg(test)]
+use eyre::eyre;
 use eyre::Result;
 
 pub const EXAMPLE: &str = "    [D]    
@@ -20,33 +21,172 @@ move 1 from 1 to 2
 
 pub type Crate = char;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct State(pub Vec<VecDeque<Crate>>);
 
 impl State {
-    pub fn from_lines(lines: Vec<Vec<Option<Crate>>>) -> Self {
-        let numlines = lines.len();
-        let numcols = lines[0].len();
+    /// Builds the state from the lines of the drawing, top first
+    ///
+    /// Lines can be shorter than `numstacks`, as when trailing spaces are
+    /// trimmed.
+    pub fn from_lines(lines: Vec<Vec<Option<Crate>>>, numstacks: usize) -> Self {
         State(
-            (0..numcols)
+            (0..numstacks)
                 .map(|col| {
-                    (0..numlines)
-                        .flat_map(|line| lines[line][col])
+                    lines
+                        .iter()
+                        .flat_map(|line| line.get(col).copied().flatten())
                         .rev()
                         .collect::<VecDeque<_>>()
                 })
                 .collect::<Vec<_>>(),
         )
     }
+
+    /// Crates at the top of each stack
+    pub fn tops(&self) -> String {
+        self.0.iter().flat_map(|stack| stack.back()).collect()
+    }
+}
+
+/// Draws the stacks like the puzzle does, with the stack numbers at the
+/// bottom
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(|stack| stack.len()).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let line = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{}]", c),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", line.join(" "))?;
+        }
+        let base = (1..=self.0.len())
+            .map(|i| format!("{:^3}", i))
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", base.join(" "))
+    }
+}
+
+/// Number of the stack at index `stack`, as in the drawing
+fn stack_number(stack: usize) -> u128 {
+    stack as u128 + 1
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Move {
     pub from: usize,
     pub to: usize,
     pub num: u32,
 }
 
+/// How many crates the crane lifts at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// One at a time, reversing their order
+    CrateMover9000,
+    /// All of them at once, keeping their order
+    CrateMover9001,
+    /// Up to k at a time, keeping the order of each batch
+    Batch(usize),
+}
+
+impl Mode {
+    fn batch(&self, num: usize) -> usize {
+        match self {
+            Mode::CrateMover9000 => 1,
+            Mode::CrateMover9001 => num,
+            Mode::Batch(k) => *k,
+        }
+    }
+}
+
+/// Crane that moves crates around, keeping the state after each step
+#[derive(Debug, Clone)]
+pub struct Crane {
+    pub mode: Mode,
+    /// Initial state first, current state last
+    history: Vec<State>,
+}
+
+impl Crane {
+    pub fn new(mode: Mode, state: State) -> Result<Crane> {
+        if mode == Mode::Batch(0) {
+            return Err(eyre!("crane can't move 0 crates at a time"));
+        }
+        Ok(Crane {
+            mode,
+            history: vec![state],
+        })
+    }
+
+    pub fn state(&self) -> &State {
+        self.history
+            .last()
+            .expect("history always has the initial state")
+    }
+
+    /// States after each move, starting with the initial one
+    pub fn history(&self) -> &[State] {
+        &self.history
+    }
+
+    pub fn apply(&mut self, mv: &Move) -> Result<()> {
+        let mut state = self.state().clone();
+        let numstacks = state.0.len();
+        for stack in [mv.from, mv.to] {
+            if stack >= numstacks {
+                return Err(eyre!(
+                    "invalid stack {} in {:?}, there are {} stacks",
+                    stack_number(stack),
+                    mv,
+                    numstacks
+                ));
+            }
+        }
+        if mv.from == mv.to {
+            return Err(eyre!(
+                "{:?} moves stack {} onto itself",
+                mv,
+                stack_number(mv.from)
+            ));
+        }
+        let num = mv.num as usize;
+        if state.0[mv.from].len() < num {
+            return Err(eyre!(
+                "can't move {} crates from stack {}, it has only {}",
+                num,
+                stack_number(mv.from),
+                state.0[mv.from].len()
+            ));
+        }
+        let batch = self.mode.batch(num);
+        let mut left = num;
+        while left > 0 {
+            let size = batch.min(left);
+            let at = state.0[mv.from].len() - size;
+            let mut lifted = state.0[mv.from].split_off(at);
+            state.0[mv.to].append(&mut lifted);
+            left -= size;
+        }
+        self.history.push(state);
+        Ok(())
+    }
+
+    /// Reverts the last move applied
+    pub fn undo(&mut self) -> Result<()> {
+        if self.history.len() == 1 {
+            return Err(eyre!("no moves to undo"));
+        }
+        self.history.pop();
+        Ok(())
+    }
+}
+
 pub mod parser {
     use eyre::eyre;
     use eyre::Result;
@@ -76,37 +216,61 @@ pub mod parser {
         branch::alt((cell_crate, cell_empty))(input)
     }
 
+    /// Line with at least one cell; lines of a drawing always have a
+    /// crate, so an empty line is the base line of a drawing without
+    /// stacks
     fn cell_line(input: &str) -> IResult<&str, Vec<Option<Crate>>> {
-        let (input, line) = multi::separated_list0(bytes::tag(" "), cell)(input)?;
+        let (input, line) = multi::separated_list1(bytes::tag(" "), cell)(input)?;
+        let (input, _) = character::space0(input)?;
         let (input, _) = character::newline(input)?;
         Ok((input, line))
     }
 
+    fn base_line(input: &str) -> IResult<&str, usize> {
+        let (input, _) = character::space0(input)?;
+        let (input, numbers) = multi::separated_list0(character::space1, character::u32)(input)?;
+        let (input, _) = character::space0(input)?;
+        let (input, _) = character::newline(input)?;
+        Ok((input, numbers.len()))
+    }
+
+    fn drawing(input: &str) -> IResult<&str, State> {
+        let (input, cell_lines) = multi::many0(cell_line)(input)?;
+        let (input, numstacks) = combinator::verify(base_line, |numstacks| {
+            cell_lines.iter().all(|line| line.len() <= *numstacks)
+        })(input)?;
+        Ok((input, State::from_lines(cell_lines, numstacks)))
+    }
+
+    /// Stack index; the input numbers them from 1
+    fn stack(input: &str) -> IResult<&str, usize> {
+        combinator::map_opt(character::u32, |n| (n as usize).checked_sub(1))(input)
+    }
+
     fn mv(input: &str) -> IResult<&str, Move> {
         let (input, _) = bytes::tag("move ")(input)?;
         let (input, num) = character::u32(input)?;
         let (input, _) = bytes::tag(" from ")(input)?;
-        let (input, from) = character::u32(input)?;
+        let (input, from) = stack(input)?;
         let (input, _) = bytes::tag(" to ")(input)?;
-        let (input, to) = character::u32(input)?;
+        let (input, to) = stack(input)?;
         let (input, _) = character::newline(input)?;
-        Ok((
-            input,
-            Move {
-                from: from as usize - 1,
-                to: to as usize - 1,
-                num,
-            },
-        ))
+        Ok((input, Move { from, to, num }))
     }
 
     fn all(input: &str) -> IResult<&str, (State, Vec<Move>)> {
-        let (input, cell_lines) = multi::many1(cell_line)(input)?;
-        let (input, _) = multi::many1(character::one_of("0123456789 "))(input)?;
-        let (input, _) = character::newline(input)?;
+        let (input, state) = drawing(input)?;
         let (input, _) = character::newline(input)?;
         let (input, moves) = multi::many1(mv)(input)?;
-        Ok((input, (State::from_lines(cell_lines), moves)))
+        Ok((input, (state, moves)))
+    }
+
+    /// Parses just the drawing of the stacks
+    pub fn parse_state(mut bufin: impl BufRead) -> Result<State> {
+        let mut input = String::default();
+        bufin.read_to_string(&mut input)?;
+        let result = combinator::all_consuming(drawing)(&input);
+        Ok(result.map_err(|e| eyre!("error reading input: {:?}", e))?.1)
     }
 
     pub fn parse(mut bufin: impl BufRead) -> Result<(State, Vec<Move>)> {
@@ -122,5 +286,84 @@ fn test() -> Result<()> {
     let (state, moves) = parser::parse(EXAMPLE.as_bytes())?;
     assert_eq!(state.0.len(), 3);
     assert_eq!(moves.len(), 4);
+    // Stacks are numbered from 1
+    let zero = EXAMPLE.replace("from 1 to 3", "from 0 to 3");
+    assert!(parser::parse(zero.as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_crane() -> Result<()> {
+    let (state, moves) = parser::parse(EXAMPLE.as_bytes())?;
+    let mut tops = vec![];
+    for mode in [Mode::CrateMover9000, Mode::CrateMover9001, Mode::Batch(2)] {
+        let mut crane = Crane::new(mode, state.clone())?;
+        for mv in &moves {
+            crane.apply(mv)?;
+        }
+        assert_eq!(crane.history().len(), moves.len() + 1);
+        tops.push(crane.state().tops());
+    }
+    assert_eq!(tops, vec!["CMZ", "MCD", "MCZ"]);
+    // Errors leave the state untouched
+    let mut crane = Crane::new(Mode::CrateMover9000, state.clone())?;
+    let mv = |num, from, to| Move { num, from, to };
+    assert!(crane.apply(&mv(4, 0, 1)).is_err());
+    assert!(crane.apply(&mv(1, 0, 3)).is_err());
+    assert!(crane.apply(&mv(1, 0, 0)).is_err());
+    assert!(crane.apply(&mv(1, usize::MAX, 0)).is_err());
+    assert_eq!(crane.state(), &state);
+    assert!(crane.undo().is_err());
+    crane.apply(&moves[0])?;
+    assert_ne!(crane.state(), &state);
+    crane.undo()?;
+    assert_eq!(crane.state(), &state);
+    Ok(())
+}
+
+#[test]
+fn test_display() -> Result<()> {
+    let (state, _) = parser::parse(EXAMPLE.as_bytes())?;
+    let drawing = state.to_string();
+    assert!(EXAMPLE.starts_with(&drawing));
+    // Editors trim trailing spaces
+    let trimmed = EXAMPLE
+        .lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect::<String>();
+    assert_eq!(parser::parse(trimmed.as_bytes())?.0, state);
+    Ok(())
+}
+
+#[test]
+fn test_roundtrip() -> Result<()> {
+    // Pseudo-random states, with up to 12 stacks and some of them empty;
+    // without stacks, the drawing is just an empty base line
+    assert_eq!(State(vec![]).to_string(), "\n");
+    assert_eq!(parser::parse_state("\n".as_bytes())?, State(vec![]));
+    let mut seed = 42_u32;
+    let mut random = |max: u32| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) % max
+    };
+    for _ in 0..200 {
+        let numstacks = random(13) as usize;
+        let state = State(
+            (0..numstacks)
+                .map(|_| {
+                    (0..random(6))
+                        .map(|_| (b'A' + random(26) as u8) as char)
+                        .collect()
+                })
+                .collect(),
+        );
+        let drawing = state.to_string();
+        assert_eq!(parser::parse_state(drawing.as_bytes())?, state);
+        let trimmed = drawing
+            .lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect::<String>();
+        assert_eq!(parser::parse_state(trimmed.as_bytes())?, state);
+    }
     Ok(())
 }
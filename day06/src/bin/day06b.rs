@@ -8,8 +8,7 @@ use std::io::{stdin, BufRead};
 use day06::*;
 
 fn process(bufin: impl BufRead) -> Result<usize> {
-    let input = parser::parse(bufin)?;
-    solve::<14>(input)
+    first_marker(bufin, 14)
 }
 
 #[test]
@@ -4,6 +4,9 @@
 
 use eyre::eyre;
 use eyre::Result;
+use std::collections::VecDeque;
+use std::io;
+use std::io::BufRead;
 
 pub const EXAMPLE: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n";
 pub const EXAMPLE2: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz\n";
@@ -23,17 +26,79 @@ pub mod parser {
     }
 }
 
-pub fn solve<const WINDOW_SIZE: usize>(input: String) -> Result<usize> {
-    let binput = input.as_bytes();
-    for istart in 0..binput.len() {
-        let mut slice: [u8; WINDOW_SIZE] = binput[istart..(istart + WINDOW_SIZE)].try_into()?;
-        slice.sort();
-        let duplicate = (1..slice.len()).any(|i| slice[i - 1] == slice[i]);
-        if !duplicate {
-            return Ok(istart + WINDOW_SIZE);
+/// Positions right after each window of `window` different bytes,
+/// read one byte at a time until the end of the line
+pub struct Markers<R> {
+    bytes: io::Bytes<R>,
+    window: usize,
+    last: VecDeque<u8>,
+    /// Number of times each byte appears in `last`
+    counts: [usize; 256],
+    /// Number of bytes that appear more than once in `last`
+    repeated: usize,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Markers<R> {
+    pub fn new(bufin: R, window: usize) -> Result<Markers<R>> {
+        if window == 0 {
+            return Err(eyre!("marker window can't be empty"));
+        }
+        Ok(Markers {
+            bytes: bufin.bytes(),
+            window,
+            last: VecDeque::with_capacity(window + 1),
+            counts: [0; 256],
+            repeated: 0,
+            pos: 0,
+            done: false,
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Markers<R> {
+    type Item = Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let byte = match self.bytes.next() {
+                None | Some(Ok(b'\n')) => {
+                    self.done = true;
+                    break;
+                }
+                Some(Ok(byte)) => byte,
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            };
+            self.pos += 1;
+            self.last.push_back(byte);
+            self.counts[byte as usize] += 1;
+            if self.counts[byte as usize] == 2 {
+                self.repeated += 1;
+            }
+            if self.last.len() > self.window {
+                let old = self.last.pop_front().unwrap() as usize;
+                self.counts[old] -= 1;
+                if self.counts[old] == 1 {
+                    self.repeated -= 1;
+                }
+            }
+            if self.last.len() == self.window && self.repeated == 0 {
+                return Some(Ok(self.pos));
+            }
         }
+        None
     }
-    Err(eyre!("marker not found"))
+}
+
+/// Position right after the first window of `window` different bytes
+pub fn first_marker(bufin: impl BufRead, window: usize) -> Result<usize> {
+    Markers::new(bufin, window)?
+        .next()
+        .unwrap_or_else(|| Err(eyre!("marker not found")))
 }
 
 #[test]
@@ -44,3 +109,25 @@ fn test() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_markers() -> Result<()> {
+    for example in [EXAMPLE, EXAMPLE2, EXAMPLE3, EXAMPLE4, EXAMPLE5] {
+        let bytes = parser::parse(example.as_bytes())?.into_bytes();
+        for window in [1, 4, 14] {
+            let expected = (window..=bytes.len())
+                .filter(|&end| {
+                    let slice = &bytes[end - window..end];
+                    (0..window).all(|i| !slice[i + 1..].contains(&slice[i]))
+                })
+                .collect::<Vec<_>>();
+            let markers = Markers::new(example.as_bytes(), window)?.collect::<Result<Vec<_>>>()?;
+            assert_eq!(markers, expected);
+        }
+    }
+    // No marker, or not enough input for a window
+    assert!(first_marker("aaaaaaaa\n".as_bytes(), 4).is_err());
+    assert!(first_marker("abc\n".as_bytes(), 4).is_err());
+    assert!(first_marker("abc".as_bytes(), 0).is_err());
+    Ok(())
+}
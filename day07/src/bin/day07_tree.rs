@@ -0,0 +1,40 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use eyre::eyre;
+use eyre::Result;
+use std::env;
+use std::io::{stdin, BufRead};
+
+use day07::filesystem::*;
+use day07::*;
+
+fn process(bufin: impl BufRead, format: &str) -> Result<String> {
+    let input = parser::parse(bufin)?;
+    let fs = Filesystem::from_cmds(&input)?;
+    match format {
+        "tree" => Ok(fs.tree()),
+        "du" => Ok(fs.du()),
+        "json" => Ok(format!("{}\n", fs.to_json())),
+        _ => Err(eyre!(
+            "invalid format {}, expected tree, du or json",
+            format
+        )),
+    }
+}
+
+#[test]
+fn test() -> Result<()> {
+    assert!(process(EXAMPLE.as_bytes(), "tree")?.starts_with("- / (dir)\n"));
+    assert!(process(EXAMPLE.as_bytes(), "du")?.ends_with("48381165\t/\n"));
+    assert!(process(EXAMPLE.as_bytes(), "json")?.starts_with("{\"a\":"));
+    assert!(process(EXAMPLE.as_bytes(), "xml").is_err());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let format = env::args().nth(1).unwrap_or_else(|| String::from("tree"));
+    print!("{}", process(stdin().lock(), &format)?);
+    Ok(())
+}
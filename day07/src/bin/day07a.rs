@@ -3,47 +3,37 @@
 // file 'LICENSE', which is part of this source code package.
 
 use eyre::Result;
-use std::collections::HashMap;
+use std::env;
 use std::io::{stdin, BufRead};
 
+use day07::filesystem::*;
 use day07::*;
 
-fn size_visitor(recentry: &RecEntry, capped: &mut u32) -> u32 {
-    match recentry.entry {
-        Entry::Dir(_) => {
-            let size = recentry
-                .children
-                .values()
-                .map(|e| size_visitor(e, capped))
-                .sum();
-            if size < 100000 {
-                *capped += size;
-            }
-            size
-        }
-        Entry::File(_, size) => size,
-    }
-}
-
-fn process(bufin: impl BufRead) -> Result<u32> {
+fn process(bufin: impl BufRead, threshold: Size) -> Result<Size> {
     let input = parser::parse(bufin)?;
-    let mut root = RecEntry {
-        entry: Entry::Dir(String::from("/")),
-        children: HashMap::default(),
-    };
-    rec_builder(&mut root, &input, 1)?;
-    let mut capped = 0;
-    size_visitor(&root, &mut capped);
-    Ok(capped)
+    let fs = Filesystem::from_cmds(&input)?;
+    Ok(fs
+        .size_index()
+        .into_values()
+        .filter(|&size| size <= threshold)
+        .sum())
 }
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(process(EXAMPLE.as_bytes())?, 95437);
+    assert_eq!(process(EXAMPLE.as_bytes(), 100000)?, 95437);
+    // Directories of exactly the threshold count
+    let transcript = "$ ls\ndir a\n$ cd a\n$ ls\n100000 f\n";
+    assert_eq!(process(transcript.as_bytes(), 100000)?, 200000);
+    assert_eq!(process(transcript.as_bytes(), 99999)?, 0);
     Ok(())
 }
 
 fn main() -> Result<()> {
-    println!("{}", process(stdin().lock())?);
+    let threshold = match env::args().nth(1) {
+        Some(arg) => arg.parse()?,
+        None => 100000,
+    };
+    println!("{}", process(stdin().lock(), threshold)?);
     Ok(())
 }
@@ -4,53 +4,37 @@
 
 use eyre::eyre;
 use eyre::Result;
-use std::collections::HashMap;
+use std::env;
 use std::io::{stdin, BufRead};
 
+use day07::cleanup::*;
+use day07::filesystem::*;
 use day07::*;
 
-pub fn size_visitor(recentry: &RecEntry, dirsize: &mut HashMap<String, u32>) -> u32 {
-    match recentry.entry {
-        Entry::Dir(ref name) => {
-            let size = recentry
-                .children
-                .values()
-                .map(|e| size_visitor(e, dirsize))
-                .sum();
-            dirsize.insert(name.to_string(), size);
-            size
-        }
-        Entry::File(_, size) => size,
-    }
-}
-
-fn process(bufin: impl BufRead) -> Result<u32> {
+fn process(bufin: impl BufRead, disk: &Disk) -> Result<Size> {
     let input = parser::parse(bufin)?;
-    let mut root = RecEntry {
-        entry: Entry::Dir(String::from("/")),
-        children: HashMap::default(),
-    };
-    rec_builder(&mut root, &input, 1)?;
-    let mut dirsize = HashMap::default();
-    size_visitor(&root, &mut dirsize);
-    let used = dirsize.get("/").ok_or_else(|| eyre!("root not found"))?;
-    let unused = 70000000 - used;
-    let needed = 30000000 - unused;
-    let toclear = dirsize
-        .into_values()
-        .filter(|&size| size >= needed)
-        .min()
-        .ok_or_else(|| eyre!("no dir with sufficient size"))?;
+    let fs = Filesystem::from_cmds(&input)?;
+    let (_, smallest) = smallest(&fs, disk)?;
+    let (_, toclear) = smallest.ok_or_else(|| eyre!("there is enough space already"))?;
     Ok(toclear)
 }
 
 #[test]
 fn test() -> Result<()> {
-    assert_eq!(process(EXAMPLE.as_bytes())?, 24933642);
+    assert_eq!(process(EXAMPLE.as_bytes(), &Disk::PUZZLE)?, 24933642);
     Ok(())
 }
 
 fn main() -> Result<()> {
-    println!("{}", process(stdin().lock())?);
+    let mut args = env::args().skip(1);
+    let disk = match (args.next(), args.next()) {
+        (Some(total), Some(required)) => Disk {
+            total: total.parse()?,
+            required: required.parse()?,
+        },
+        (None, None) => Disk::PUZZLE,
+        _ => return Err(eyre!("usage: day07b [total required]")),
+    };
+    println!("{}", process(stdin().lock(), &disk)?);
     Ok(())
 }
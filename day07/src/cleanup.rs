@@ -0,0 +1,390 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Planning which directories to delete to make room for the update.
+
+use eyre::eyre;
+use eyre::Result;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use super::filesystem::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disk {
+    pub total: Size,
+    /// Unused space required by the update
+    pub required: Size,
+}
+
+impl Disk {
+    pub const PUZZLE: Disk = Disk {
+        total: 70000000,
+        required: 30000000,
+    };
+
+    /// Space we have to free when `used` is in use
+    pub fn needed(&self, used: Size) -> Result<Size> {
+        let unused = self
+            .total
+            .checked_sub(used)
+            .ok_or_else(|| eyre!("{} used in a disk of {}", used, self.total))?;
+        Ok(self.required.saturating_sub(unused))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cleanup {
+    pub needed: Size,
+    /// Smallest directory that frees enough space by itself
+    pub smallest: Option<(String, Size)>,
+    /// Directories, none inside another, that together free enough space
+    /// while deleting as little as possible
+    pub minimal: Vec<(String, Size)>,
+}
+
+/// Space we have to free, and the smallest directory that frees it by
+/// itself, or `None` if there's enough space already
+///
+/// Unlike [`Cleanup::new`], this doesn't look for sets of directories.
+pub fn smallest(fs: &Filesystem, disk: &Disk) -> Result<(Size, Option<(String, Size)>)> {
+    let index = fs.size_index();
+    let used = *index.get("/").ok_or_else(|| eyre!("root not found"))?;
+    let needed = disk.needed(used)?;
+    if needed == 0 {
+        return Ok((needed, None));
+    }
+    let smallest = index
+        .iter()
+        .filter(|(_, &size)| size >= needed)
+        .min_by_key(|(_, &size)| size)
+        .map(|(path, &size)| (path.clone(), size))
+        .ok_or_else(|| eyre!("can't free {}, disk has only {} used", needed, used))?;
+    Ok((needed, Some(smallest)))
+}
+
+impl Cleanup {
+    /// Plans the cleanup, failing if there are too many combinations of
+    /// directories to search for the minimal one
+    pub fn new(fs: &Filesystem, disk: &Disk) -> Result<Cleanup> {
+        let (needed, smallest) = smallest(fs, disk)?;
+        let Some(smallest) = smallest else {
+            return Ok(Cleanup {
+                needed,
+                smallest: None,
+                minimal: vec![],
+            });
+        };
+        let dirs = preorder(fs);
+        let mut search = Search::new(&dirs);
+        let minimal = match search.least_below(needed, smallest.1)? {
+            Some(chosen) => chosen
+                .iter()
+                .map(|&i| (dirs[i].0.clone(), dirs[i].1))
+                .collect(),
+            None => vec![smallest.clone()],
+        };
+        Ok(Cleanup {
+            needed,
+            smallest: Some(smallest),
+            minimal,
+        })
+    }
+
+    /// Space freed by deleting the directories in [`Cleanup::minimal`]
+    pub fn freed(&self) -> Size {
+        self.minimal.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Directories in preorder, with their sizes and the index right after
+/// their last subdirectory
+fn preorder(fs: &Filesystem) -> Vec<(String, Size, usize)> {
+    let mut dirs = fs
+        .entries()
+        .into_iter()
+        .filter(|(_, node, _)| node.is_dir())
+        .map(|(path, _, size)| (path, size, 0))
+        .collect::<Vec<_>>();
+    for i in 0..dirs.len() {
+        let prefix = path_join(&dirs[i].0, "");
+        let end = (i + 1..dirs.len())
+            .find(|&j| !dirs[j].0.starts_with(&prefix))
+            .unwrap_or(dirs.len());
+        dirs[i].2 = end;
+    }
+    dirs
+}
+
+/// Largest amount for which [`Search::least_below`] uses bitsets; the
+/// puzzle's disk has 70000000
+///
+/// Each set takes up to 16MiB, and there is one alive for the directory
+/// being processed and for each of its ancestors, so the memory used
+/// grows with the depth of the tree.
+const BITSET_MAX: Size = 1 << 27;
+
+/// Most calls of [`Search::bound`] before giving up, as the number of
+/// combinations grows exponentially with the number of directories
+const BOUND_MAX_STEPS: usize = 1 << 22;
+
+/// Search over the directories in preorder: each one is either deleted,
+/// skipping its subdirectories, or kept
+struct Search<'a> {
+    dirs: &'a [(String, Size, usize)],
+    /// Most we can free with the directories from each index on
+    maxrest: Vec<Size>,
+    current: Vec<usize>,
+    /// (index, amount) states that can't free the amount exactly
+    failed: HashSet<(usize, Size)>,
+}
+
+impl<'a> Search<'a> {
+    fn new(dirs: &'a [(String, Size, usize)]) -> Search<'a> {
+        let mut maxrest = vec![0; dirs.len() + 1];
+        for i in (0..dirs.len()).rev() {
+            maxrest[i] = maxrest[i + 1].max(dirs[i].1 + maxrest[dirs[i].2]);
+        }
+        Search {
+            dirs,
+            maxrest,
+            current: vec![],
+            failed: HashSet::new(),
+        }
+    }
+
+    /// Directories that free at least `needed` while freeing as little as
+    /// possible, if that's less than `cap`
+    ///
+    /// Uses bitsets when `cap` is up to [`BITSET_MAX`], as they take
+    /// memory proportional to it, and a limited branch-and-bound
+    /// otherwise.
+    fn least_below(&mut self, needed: Size, cap: Size) -> Result<Option<Vec<usize>>> {
+        if cap <= BITSET_MAX {
+            let target = self.least_freeable(needed, cap);
+            return Ok((target < cap && self.find(0, target)).then(|| self.current.clone()));
+        }
+        let mut best = (cap, None);
+        let mut steps = 0;
+        self.bound(0, 0, needed, &mut best, &mut steps)?;
+        Ok(best.1)
+    }
+
+    /// Lowers `best` to the directories that free the least amount at
+    /// least `needed`, with the ones from `i` on, having freed `freed`
+    /// with the ones in `current`
+    fn bound(
+        &mut self,
+        i: usize,
+        freed: Size,
+        needed: Size,
+        best: &mut (Size, Option<Vec<usize>>),
+        steps: &mut usize,
+    ) -> Result<()> {
+        *steps += 1;
+        if *steps > BOUND_MAX_STEPS {
+            return Err(eyre!(
+                "too many combinations of {} directories to search",
+                self.dirs.len()
+            ));
+        }
+        if freed >= needed {
+            if freed < best.0 {
+                *best = (freed, Some(self.current.clone()));
+            }
+            return Ok(());
+        }
+        if i == self.dirs.len() || freed + self.maxrest[i] < needed {
+            return Ok(());
+        }
+        let (_, size, end) = self.dirs[i];
+        if freed + size < best.0 {
+            self.current.push(i);
+            self.bound(end, freed + size, needed, best, steps)?;
+            self.current.pop();
+        }
+        self.bound(i + 1, freed, needed, best, steps)
+    }
+
+    /// Least amount at least `needed` that we can free exactly, or `cap`
+    ///
+    /// Subset-sum over bitsets of the amounts below `cap`, from the last
+    /// directory to the first, keeping only the sets still referenced.
+    fn least_freeable(&self, needed: Size, cap: Size) -> Size {
+        let n = self.dirs.len();
+        let words = cap as usize / 64 + 1;
+        // Each set is used last by the directory right before it, or by
+        // the first directory whose subdirectories end at it
+        let mut lastuse = (0..=n).map(|p| p.saturating_sub(1)).collect::<Vec<_>>();
+        for (i, (_, _, end)) in self.dirs.iter().enumerate() {
+            lastuse[*end] = lastuse[*end].min(i);
+        }
+        let mut sets = HashMap::<usize, Vec<u64>>::new();
+        let mut last = vec![0_u64; words];
+        last[0] = 1;
+        sets.insert(n, last);
+        for i in (0..n).rev() {
+            let (_, size, end) = self.dirs[i];
+            let mut set = sets[&(i + 1)].clone();
+            if size < cap {
+                let (wordshift, bitshift) = (size as usize / 64, size as u32 % 64);
+                let other = &sets[&end];
+                for w in (wordshift..words).rev() {
+                    let src = w - wordshift;
+                    let mut bits = other[src] << bitshift;
+                    if bitshift > 0 && src > 0 {
+                        bits |= other[src - 1] >> (64 - bitshift);
+                    }
+                    set[w] |= bits;
+                }
+            }
+            sets.retain(|p, _| lastuse[*p] < i);
+            sets.insert(i, set);
+        }
+        let set = &sets[&0];
+        (needed..cap)
+            .find(|&amount| set[amount as usize / 64] & (1 << (amount % 64)) != 0)
+            .unwrap_or(cap)
+    }
+
+    /// Looks for directories that free exactly `amount`, leaving them in
+    /// `current`
+    fn find(&mut self, i: usize, amount: Size) -> bool {
+        if amount == 0 {
+            return true;
+        }
+        if i == self.dirs.len() || amount > self.maxrest[i] || self.failed.contains(&(i, amount)) {
+            return false;
+        }
+        let (_, size, end) = self.dirs[i];
+        if size <= amount {
+            self.current.push(i);
+            if self.find(end, amount - size) {
+                return true;
+            }
+            self.current.pop();
+        }
+        if self.find(i + 1, amount) {
+            return true;
+        }
+        self.failed.insert((i, amount));
+        false
+    }
+}
+
+#[test]
+fn test() -> Result<()> {
+    let fs = Filesystem::from_cmds(&super::parser::parse(super::EXAMPLE.as_bytes())?)?;
+    let cleanup = Cleanup::new(&fs, &Disk::PUZZLE)?;
+    assert_eq!(cleanup.needed, 8381165);
+    assert_eq!(cleanup.smallest, Some(("/d".to_string(), 24933642)));
+    assert_eq!(cleanup.minimal, vec![("/d".to_string(), 24933642)]);
+    // Enough space already
+    let disk = Disk {
+        total: 100000000,
+        required: 1,
+    };
+    assert_eq!(Cleanup::new(&fs, &disk)?.smallest, None);
+    // More than the whole disk
+    let disk = Disk {
+        total: 1000,
+        required: 1,
+    };
+    assert!(Cleanup::new(&fs, &disk).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_minimal() -> Result<()> {
+    let transcript = "$ ls
+dir x
+dir y
+dir z
+$ cd x
+$ ls
+5 f
+$ cd /y
+$ ls
+dir x
+$ cd x
+$ ls
+5 f
+$ cd /z
+$ ls
+12 f
+";
+    let fs = Filesystem::from_cmds(&super::parser::parse(transcript.as_bytes())?)?;
+    // Directories with the same name don't collide
+    let index = fs.size_index();
+    assert_eq!(index["/x"], 5);
+    assert_eq!(index["/y/x"], 5);
+    // 22 used, 8 unused, so we need 9
+    let disk = Disk {
+        total: 30,
+        required: 17,
+    };
+    let cleanup = Cleanup::new(&fs, &disk)?;
+    assert_eq!(cleanup.needed, 9);
+    assert_eq!(cleanup.smallest, Some(("/z".to_string(), 12)));
+    assert_eq!(
+        cleanup.minimal,
+        vec![("/x".to_string(), 5), ("/y".to_string(), 5)]
+    );
+    assert_eq!(cleanup.freed(), 10);
+    Ok(())
+}
+
+#[test]
+fn test_large() -> Result<()> {
+    // Same layout as test_minimal, with sizes too large for the bitsets
+    let t = 1_000_000_000_u64;
+    let files = |n: usize| {
+        (0..n)
+            .map(|i| format!("{} f{}\n", t, i))
+            .collect::<String>()
+    };
+    let transcript = format!(
+        "$ ls\ndir x\ndir y\ndir z\n$ cd x\n$ ls\n{}$ cd /y\n$ ls\ndir x\n$ cd x\n$ ls\n{}$ cd /z\n$ ls\n{}",
+        files(5),
+        files(5),
+        files(12)
+    );
+    let fs = Filesystem::from_cmds(&super::parser::parse(transcript.as_bytes())?)?;
+    let disk = Disk {
+        total: 30 * t,
+        required: 17 * t,
+    };
+    let cleanup = Cleanup::new(&fs, &disk)?;
+    assert_eq!(cleanup.needed, 9 * t);
+    assert_eq!(cleanup.smallest, Some(("/z".to_string(), 12 * t)));
+    assert_eq!(
+        cleanup.minimal,
+        vec![("/x".to_string(), 5 * t), ("/y".to_string(), 5 * t)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_too_many() -> Result<()> {
+    // 40 directories of about 1GB, that need 20.5 of them deleted
+    let t = 1_000_000_000_u64;
+    let transcript = (0..40)
+        .map(|i| format!("$ cd /d{}\n$ ls\n{} f\n", i, t + i))
+        .collect::<String>();
+    let transcript = format!(
+        "$ ls\n{}{}",
+        (0..40).map(|i| format!("dir d{}\n", i)).collect::<String>(),
+        transcript
+    );
+    let fs = Filesystem::from_cmds(&super::parser::parse(transcript.as_bytes())?)?;
+    let used = 40 * t + 780;
+    let disk = Disk {
+        total: used,
+        required: 20 * t + t / 2,
+    };
+    // The smallest directory is still quick to find
+    assert_eq!(smallest(&fs, &disk)?.1, Some(("/".to_string(), used)));
+    assert!(Cleanup::new(&fs, &disk).is_err());
+    Ok(())
+}
@@ -0,0 +1,442 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Filesystem tree rebuilt from the shell transcript, with absolute
+//! paths like `/a/e`.
+
+use eyre::eyre;
+use eyre::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::*;
+
+pub type Size = u64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    File(Size),
+    Dir(BTreeMap<String, Node>),
+}
+
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Node::Dir(_))
+    }
+
+    /// Size of the file, or of everything under the directory
+    pub fn size(&self) -> Size {
+        match self {
+            Node::File(size) => *size,
+            Node::Dir(children) => children.values().map(Node::size).sum(),
+        }
+    }
+}
+
+impl From<&RecEntry> for Node {
+    fn from(recentry: &RecEntry) -> Self {
+        match recentry.entry {
+            Entry::File(_, size) => Node::File(size as Size),
+            Entry::Dir(_) => Node::Dir(
+                recentry
+                    .children
+                    .iter()
+                    .map(|(name, child)| (name.clone(), Node::from(child)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Joins a directory path and the name of an entry in it
+pub fn path_join(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn path_components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|c| !c.is_empty())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filesystem {
+    pub root: Node,
+    /// Current directory, as the names from the root
+    cwd: Vec<String>,
+}
+
+impl Default for Filesystem {
+    fn default() -> Self {
+        Filesystem {
+            root: Node::Dir(BTreeMap::new()),
+            cwd: vec![],
+        }
+    }
+}
+
+impl Filesystem {
+    pub fn new() -> Filesystem {
+        Filesystem::default()
+    }
+
+    pub fn from_cmds(cmds: &[Cmd]) -> Result<Filesystem> {
+        let mut fs = Filesystem::new();
+        for cmd in cmds {
+            fs.run(cmd)?;
+        }
+        Ok(fs)
+    }
+
+    pub fn cwd(&self) -> String {
+        format!("/{}", self.cwd.join("/"))
+    }
+
+    /// Directory at `names`, creating the missing ones
+    fn dir_mut(&mut self, names: &[String]) -> Result<&mut BTreeMap<String, Node>> {
+        let mut path = String::from("/");
+        let Node::Dir(root) = &mut self.root else {
+            return Err(eyre!("root is not a directory"));
+        };
+        let mut children = root;
+        for name in names {
+            path = path_join(&path, name);
+            let node = children
+                .entry(name.clone())
+                .or_insert_with(|| Node::Dir(BTreeMap::new()));
+            children = match node {
+                Node::Dir(grandchildren) => grandchildren,
+                Node::File(_) => return Err(eyre!("{} is a file, not a directory", path)),
+            };
+        }
+        Ok(children)
+    }
+
+    /// Applies a command of the transcript
+    ///
+    /// `cd` accepts absolute and relative paths, and creates the
+    /// directories we haven't seen in an `ls` yet; repeated `ls` of the
+    /// same directory merge their entries, failing if they conflict.
+    pub fn run(&mut self, cmd: &Cmd) -> Result<()> {
+        match cmd {
+            Cmd::Cd(dir) => {
+                let mut cwd = if dir.starts_with('/') {
+                    vec![]
+                } else {
+                    self.cwd.clone()
+                };
+                for component in path_components(dir) {
+                    match component {
+                        "." => {}
+                        ".." => {
+                            cwd.pop();
+                        }
+                        name => cwd.push(name.to_string()),
+                    }
+                }
+                self.dir_mut(&cwd)?;
+                self.cwd = cwd;
+            }
+            Cmd::Ls(entries) => {
+                let cwdpath = self.cwd();
+                let cwd = self.cwd.clone();
+                let children = self.dir_mut(&cwd)?;
+                for entry in entries {
+                    let new = match entry {
+                        Entry::Dir(_) => Node::Dir(BTreeMap::new()),
+                        Entry::File(_, size) => Node::File(*size as Size),
+                    };
+                    let node = children
+                        .entry(entry.name().to_string())
+                        .or_insert_with(|| new.clone());
+                    let conflict = match (&*node, &new) {
+                        (Node::Dir(_), Node::Dir(_)) => false,
+                        (Node::File(old), Node::File(size)) => old != size,
+                        _ => true,
+                    };
+                    if conflict {
+                        return Err(eyre!(
+                            "conflicting entries for {}: {:?} and {:?}",
+                            path_join(&cwdpath, entry.name()),
+                            node,
+                            entry
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Node at the absolute `path`
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        let mut node = &self.root;
+        for name in path_components(path) {
+            match node {
+                Node::Dir(children) => node = children.get(name)?,
+                Node::File(_) => return None,
+            }
+        }
+        Some(node)
+    }
+
+    pub fn size(&self, path: &str) -> Result<Size> {
+        Ok(self
+            .get(path)
+            .ok_or_else(|| eyre!("{} not found", path))?
+            .size())
+    }
+
+    /// All entries with their paths and sizes, parents before children
+    /// and siblings sorted by name
+    pub fn entries(&self) -> Vec<(String, &Node, Size)> {
+        fn collect<'a>(
+            path: String,
+            node: &'a Node,
+            entries: &mut Vec<(String, &'a Node, Size)>,
+        ) -> Size {
+            let i = entries.len();
+            entries.push((path.clone(), node, 0));
+            let size = match node {
+                Node::File(size) => *size,
+                Node::Dir(children) => children
+                    .iter()
+                    .map(|(name, child)| collect(path_join(&path, name), child, entries))
+                    .sum(),
+            };
+            entries[i].2 = size;
+            size
+        }
+        let mut entries = vec![];
+        collect(String::from("/"), &self.root, &mut entries);
+        entries
+    }
+
+    /// Size of each directory, keyed by its full path
+    pub fn size_index(&self) -> BTreeMap<String, Size> {
+        self.entries()
+            .into_iter()
+            .filter(|(_, node, _)| node.is_dir())
+            .map(|(path, _, size)| (path, size))
+            .collect()
+    }
+
+    /// Directories and their sizes, smallest first
+    pub fn dirs_by_size(&self) -> Vec<(String, Size)> {
+        let mut dirs = self
+            .entries()
+            .into_iter()
+            .filter(|(_, node, _)| node.is_dir())
+            .map(|(path, _, size)| (path, size))
+            .collect::<Vec<_>>();
+        dirs.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        dirs
+    }
+
+    /// Paths of the entries that satisfy `predicate`, given the path,
+    /// node and size of each
+    pub fn find(&self, predicate: impl Fn(&str, &Node, Size) -> bool) -> Vec<String> {
+        self.entries()
+            .into_iter()
+            .filter(|(path, node, size)| predicate(path, node, *size))
+            .map(|(path, _, _)| path)
+            .collect()
+    }
+
+    /// Indented listing, as in the puzzle
+    pub fn tree(&self) -> String {
+        fn rec(out: &mut String, depth: usize, name: &str, node: &Node) {
+            let indent = "  ".repeat(depth);
+            match node {
+                Node::File(size) => {
+                    writeln!(out, "{}- {} (file, size={})", indent, name, size).unwrap();
+                }
+                Node::Dir(children) => {
+                    writeln!(out, "{}- {} (dir)", indent, name).unwrap();
+                    for (childname, child) in children {
+                        rec(out, depth + 1, childname, child);
+                    }
+                }
+            }
+        }
+        let mut out = String::new();
+        rec(&mut out, 0, "/", &self.root);
+        out
+    }
+
+    /// Cumulative size of every entry, children before their directory,
+    /// like `du -a`
+    pub fn du(&self) -> String {
+        fn rec(out: &mut String, path: &str, node: &Node) -> Size {
+            let size = match node {
+                Node::File(size) => *size,
+                Node::Dir(children) => children
+                    .iter()
+                    .map(|(name, child)| rec(out, &path_join(path, name), child))
+                    .sum(),
+            };
+            writeln!(out, "{}\t{}", size, path).unwrap();
+            size
+        }
+        let mut out = String::new();
+        rec(&mut out, "/", &self.root);
+        out
+    }
+
+    /// JSON with directories as objects and files as their sizes
+    pub fn to_json(&self) -> String {
+        fn rec(out: &mut String, node: &Node) {
+            match node {
+                Node::File(size) => write!(out, "{}", size).unwrap(),
+                Node::Dir(children) => {
+                    out.push('{');
+                    for (i, (name, child)) in children.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        json_string(out, name);
+                        out.push(':');
+                        rec(out, child);
+                    }
+                    out.push('}');
+                }
+            }
+        }
+        let mut out = String::new();
+        rec(&mut out, &self.root);
+        out
+    }
+}
+
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[test]
+fn test() -> Result<()> {
+    let fs = Filesystem::from_cmds(&parser::parse(EXAMPLE.as_bytes())?)?;
+    assert_eq!(fs.cwd(), "/d");
+    assert_eq!(fs.size("/")?, 48381165);
+    assert_eq!(fs.size("/a")?, 94853);
+    assert_eq!(fs.size("/a/e/")?, 584);
+    assert_eq!(fs.size("/d/j")?, 4060174);
+    assert!(fs.size("/x").is_err());
+    assert_eq!(
+        fs.dirs_by_size(),
+        vec![
+            ("/a/e".to_string(), 584),
+            ("/a".to_string(), 94853),
+            ("/d".to_string(), 24933642),
+            ("/".to_string(), 48381165),
+        ]
+    );
+    assert_eq!(
+        fs.find(|_, node, size| !node.is_dir() && size > 8000000),
+        vec!["/b.txt", "/c.dat", "/d/d.log"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge() -> Result<()> {
+    let transcript = "$ cd /a/b
+$ ls
+1 x
+$ cd /
+$ ls
+dir a
+2 y
+$ cd a
+$ ls
+dir b
+$ cd b
+$ ls
+1 x
+3 z
+";
+    let fs = Filesystem::from_cmds(&parser::parse(transcript.as_bytes())?)?;
+    assert_eq!(
+        fs.find(|_, _, _| true),
+        vec!["/", "/a", "/a/b", "/a/b/x", "/a/b/z", "/y"]
+    );
+    assert_eq!(fs.size("/")?, 6);
+    // Same name as file and dir, and same file with different sizes
+    for conflict in [
+        "$ ls\ndir a\n$ ls\n1 a\n",
+        "$ ls\n1 a\n$ cd a\n",
+        "$ ls\n1 a\n$ ls\n2 a\n",
+    ] {
+        assert!(Filesystem::from_cmds(&parser::parse(conflict.as_bytes())?).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_output() -> Result<()> {
+    let cmds = parser::parse(EXAMPLE.as_bytes())?;
+    let fs = Filesystem::from_cmds(&cmds)?;
+    assert_eq!(
+        fs.tree(),
+        "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+    );
+    assert_eq!(
+        fs.du(),
+        "584\t/a/e/i
+584\t/a/e
+29116\t/a/f
+2557\t/a/g
+62596\t/a/h.lst
+94853\t/a
+14848514\t/b.txt
+8504156\t/c.dat
+5626152\t/d/d.ext
+8033020\t/d/d.log
+4060174\t/d/j
+7214296\t/d/k
+24933642\t/d
+48381165\t/
+"
+    );
+    assert_eq!(
+        fs.to_json(),
+        concat!(
+            r#"{"a":{"e":{"i":584},"f":29116,"g":2557,"h.lst":62596},"#,
+            r#""b.txt":14848514,"c.dat":8504156,"#,
+            r#""d":{"d.ext":5626152,"d.log":8033020,"j":4060174,"k":7214296}}"#
+        )
+    );
+    let mut quoted = String::new();
+    json_string(&mut quoted, "a\"b\\c\n");
+    assert_eq!(quoted, r#""a\"b\\c\u000a""#);
+    // The tree built by rec_builder renders the same
+    let mut root = RecEntry::from(&Entry::Dir(String::from("/")));
+    rec_builder(&mut root, &cmds, 1)?;
+    assert_eq!(Node::from(&root), fs.root);
+    Ok(())
+}
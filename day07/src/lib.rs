@@ -4,7 +4,10 @@
 
 use eyre::eyre;
 use eyre::Result;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+pub mod cleanup;
+pub mod filesystem;
 
 pub const EXAMPLE: &str = "$ cd /
 $ ls
@@ -129,14 +132,14 @@ fn test() -> Result<()> {
 #[derive(Debug)]
 pub struct RecEntry {
     pub entry: Entry,
-    pub children: HashMap<String, RecEntry>,
+    pub children: BTreeMap<String, RecEntry>,
 }
 
 impl From<&Entry> for RecEntry {
     fn from(entry: &Entry) -> Self {
         Self {
             entry: entry.clone(),
-            children: HashMap::default(),
+            children: BTreeMap::default(),
         }
     }
 }
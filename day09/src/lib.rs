@@ -13,6 +13,7 @@ pub type Sqrid = sqrid::sqrid_create!(1000, 1000, true);
 pub type Qa = sqrid::qa_create!(Sqrid);
 pub type Qr = sqrid::qr::Qr;
 pub type Grid = sqrid::grid_create!(Sqrid, char);
+#[allow(clippy::manual_div_ceil)]
 pub type Gridbool = sqrid::gridbool_create!(Sqrid);
 
 #[derive(Debug)]
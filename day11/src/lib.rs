@@ -150,7 +150,7 @@ pub fn monkey_business<const ROUNDS: usize, const DIVIDER: Worry>(
             while let Some(item) = monkeys[i].items.pop_front() {
                 monkeys[i].inspected += 1;
                 let worry = (monkeys[i].op.apply(item) / DIVIDER) % module;
-                let target_monkey = if worry % monkeys[i].test == 0 {
+                let target_monkey = if worry.is_multiple_of(monkeys[i].test) {
                     monkeys[i].monkey_true
                 } else {
                     monkeys[i].monkey_false
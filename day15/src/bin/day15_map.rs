@@ -0,0 +1,29 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use std::env;
+use std::io::{stdin, BufRead};
+
+use day15::*;
+
+fn process(config: &Config, bufin: impl BufRead) -> Result<String> {
+    let input = parser::parse(bufin)?;
+    render(&input, &config.area())
+}
+
+#[test]
+fn test() -> Result<()> {
+    let map = process(&Config::EXAMPLE, EXAMPLE.as_bytes())?;
+    assert_eq!(map.lines().count(), 21);
+    assert_eq!(map.lines().nth(11), Some("S#############.######"));
+    assert!(process(&Config::REAL, EXAMPLE.as_bytes()).is_err());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let config = Config::from_args(env::args().skip(1))?;
+    print!("{}", process(&config, stdin().lock())?);
+    Ok(())
+}
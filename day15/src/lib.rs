@@ -2,8 +2,12 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
-#[cfg(test)]
-use color_eyre::Result;
+pub use color_eyre::{eyre::eyre, Result};
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+
+pub use aoc::interval::{Interval, IntervalSet};
+pub use aoc::point::Point2;
 
 pub const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
@@ -21,7 +25,94 @@ Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 ";
 
-pub type Xy = (i64, i64);
+pub type Xy = Point2<i64>;
+
+/// Runtime parameters of the puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Row evaluated in part 1
+    pub row: i64,
+    /// Part 2 searches in `[0, max]x[0, max]`
+    pub max: i64,
+    /// Multiplier of x in the tuning frequency
+    pub tuning: i64,
+    /// Area rendered by the coverage map, as top-left and bottom-right
+    /// corners; defaults to the part 2 search area
+    pub area: Option<(Xy, Xy)>,
+}
+
+impl Config {
+    pub const REAL: Config = Config {
+        row: 2000000,
+        max: 4000000,
+        tuning: 4000000,
+        area: None,
+    };
+    pub const EXAMPLE: Config = Config {
+        row: 10,
+        max: 20,
+        tuning: 4000000,
+        area: None,
+    };
+
+    /// Parses `--row N`, `--max N`, `--tuning N` and
+    /// `--area X0,Y0,X1,Y1` over the values of [`Config::REAL`]
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Config> {
+        let mut config = Config::REAL;
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| eyre!("missing value for {}", flag))?;
+            match flag.as_str() {
+                "--row" => config.row = value.parse()?,
+                "--max" => config.max = value.parse()?,
+                "--tuning" => config.tuning = value.parse()?,
+                "--area" => {
+                    let v = value
+                        .split(',')
+                        .map(|n| n.parse())
+                        .collect::<Result<Vec<i64>, _>>()?;
+                    if v.len() != 4 || v[0] > v[2] || v[1] > v[3] {
+                        return Err(eyre!("invalid area {}", value));
+                    }
+                    config.area = Some((Xy::new([v[0], v[1]]), Xy::new([v[2], v[3]])));
+                }
+                _ => return Err(eyre!("unknown argument {}", flag)),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn area(&self) -> (Xy, Xy) {
+        self.area
+            .unwrap_or((Xy::zero(), Xy::new([self.max, self.max])))
+    }
+
+    pub fn tuning_frequency(&self, xy: &Xy) -> i64 {
+        xy.x() * self.tuning + xy.y()
+    }
+}
+
+#[test]
+fn test_config() -> Result<()> {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(Config::from_args(vec![])?, Config::REAL);
+    assert_eq!(
+        Config::from_args(args("--row 10 --max 20"))?,
+        Config {
+            area: None,
+            ..Config::EXAMPLE
+        }
+    );
+    let config = Config::from_args(args("--area -4,-2,30,25 --tuning 10"))?;
+    assert_eq!(config.area(), ((-4, -2).into(), (30, 25).into()));
+    assert_eq!(config.tuning_frequency(&(14, 11).into()), 151);
+    assert!(Config::from_args(args("--row")).is_err());
+    assert!(Config::from_args(args("--col 3")).is_err());
+    assert!(Config::from_args(args("--area 1,2,3")).is_err());
+    Ok(())
+}
 
 pub mod parser {
     use aoc::parser::*;
@@ -33,7 +124,7 @@ pub mod parser {
         let (input, x) = character::i64(input)?;
         let (input, _) = bytes::tag(", y=")(input)?;
         let (input, y) = character::i64(input)?;
-        Ok((input, (x, y)))
+        Ok((input, Xy::new([x, y])))
     }
 
     fn line(input: &str) -> IResult<&str, (Xy, Xy)> {
@@ -56,6 +147,197 @@ fn test() -> Result<()> {
     Ok(())
 }
 
-pub fn manhattan_distance(p1: &Xy, p2: &Xy) -> i64 {
-    (p1.0 - p2.0).abs() + (p1.1 - p2.1).abs()
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Sensor {
+    pub center: Xy,
+    pub range: i64,
+}
+
+impl Sensor {
+    pub fn new(center: Xy, range: i64) -> Sensor {
+        Sensor { center, range }
+    }
+    pub fn covered(&self, xy: &Xy) -> bool {
+        self.center.manhattan(xy) <= self.range
+    }
+    pub fn row_interval(&self, y: i64) -> Option<Interval<i64>> {
+        let half = self.range - (y - self.center.y()).abs();
+        Interval::try_new(self.center.x() - half, self.center.x() + half)
+    }
+    pub fn iter_outerlimits(&self) -> impl Iterator<Item = Xy> + '_ {
+        (0..=self.range)
+            .map(move |d| {
+                // from N to E
+                Xy::new([self.center.x() + d, self.center.y() - self.range - 1 + d])
+            })
+            .chain((0..=self.range).map(move |d| {
+                // from E to S
+                Xy::new([self.center.x() + self.range + 1 - d, self.center.y() + d])
+            }))
+            .chain((0..=self.range).map(move |d| {
+                // from S to W
+                Xy::new([self.center.x() - d, self.center.y() + self.range + 1 - d])
+            }))
+            .chain((0..=self.range).map(move |d| {
+                // from W to N
+                Xy::new([self.center.x() - self.range - 1 + d, self.center.y() - d])
+            }))
+    }
+}
+
+#[test]
+fn test_iter_outerlimits() {
+    let sensor = Sensor::new(Xy::zero(), 0);
+    let limits_exc = sensor.iter_outerlimits().collect::<Vec<_>>();
+    for xy in &limits_exc {
+        assert!(!sensor.covered(xy));
+        assert_eq!(sensor.center.manhattan(xy), sensor.range + 1);
+    }
+    assert_eq!(limits_exc, [(0, -1), (1, 0), (0, 1), (-1, 0)].map(Xy::from));
+    let sensor = Sensor::new(Xy::zero(), 1);
+    let limits_exc = sensor.iter_outerlimits().collect::<Vec<_>>();
+    for xy in &limits_exc {
+        assert!(!sensor.covered(xy));
+        assert_eq!(sensor.center.manhattan(xy), sensor.range + 1);
+    }
+    assert_eq!(
+        limits_exc,
+        [
+            (0, -2),
+            (1, -1),
+            (2, 0),
+            (1, 1),
+            (0, 2),
+            (-1, 1),
+            (-2, 0),
+            (-1, -1)
+        ]
+        .map(Xy::from)
+    );
+}
+
+impl From<(Xy, Xy)> for Sensor {
+    fn from(input: (Xy, Xy)) -> Self {
+        Sensor::new(input.0, input.0.manhattan(&input.1))
+    }
+}
+
+/// Set of x covered by the sensors in row `y`
+pub fn row_coverage(sensors: &[Sensor], y: i64) -> IntervalSet<i64> {
+    sensors.iter().filter_map(|s| s.row_interval(y)).collect()
+}
+
+/// Finds a point in `[0, max]x[0, max]` that no sensor covers
+///
+/// An isolated uncovered point must lie just outside the diamond of
+/// some sensors. In rotated coordinates `u = x + y`, `v = x - y` those
+/// diamond boundaries are straight lines, so the candidates are their
+/// crossings with each other and with the edges of the search area.
+pub fn find_uncovered(sensors: &[Sensor], max: i64) -> Option<Xy> {
+    let mut us = BTreeSet::<i64>::new();
+    let mut vs = BTreeSet::<i64>::new();
+    for s in sensors {
+        let (u, v) = (s.center.x() + s.center.y(), s.center.x() - s.center.y());
+        us.extend([u - s.range - 1, u + s.range + 1]);
+        vs.extend([v - s.range - 1, v + s.range + 1]);
+    }
+    let area = (Xy::zero(), Xy::new([max, max]));
+    let mut candidates = vec![(0, 0), (0, max), (max, 0), (max, max)];
+    for u in &us {
+        for v in &vs {
+            if (u + v) % 2 == 0 {
+                candidates.push(((u + v) / 2, (u - v) / 2));
+            }
+        }
+        for e in [0, max] {
+            candidates.extend([(e, u - e), (u - e, e)]);
+        }
+    }
+    for v in &vs {
+        for e in [0, max] {
+            candidates.extend([(e, e - v), (v + e, e)]);
+        }
+    }
+    candidates
+        .into_iter()
+        .map(Xy::from)
+        .find(|xy| xy.is_inside(&area) && sensors.iter().all(|s| !s.covered(xy)))
+}
+
+#[test]
+fn test_row_coverage() -> Result<()> {
+    let sensors = parser::parse(EXAMPLE.as_bytes())?
+        .into_iter()
+        .map(Sensor::from)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        row_coverage(&sensors, 10).iter().collect::<Vec<_>>(),
+        vec![&Interval::new(-2, 24)]
+    );
+    assert_eq!(
+        row_coverage(&sensors, 11).iter().collect::<Vec<_>>(),
+        vec![&Interval::new(-3, 13), &Interval::new(15, 25)]
+    );
+    assert_eq!(find_uncovered(&sensors, 20), Some((14, 11).into()));
+    Ok(())
+}
+
+/// Most cells [`render`] draws, enough for a few screens of map
+pub const RENDER_MAX_CELLS: i64 = 1 << 20;
+
+/// Renders the coverage map of the area like the puzzle does: sensors
+/// as `S`, beacons as `B`, covered positions as `#`
+///
+/// Fails for areas larger than [`RENDER_MAX_CELLS`], such as the whole
+/// part 2 search area of a real input.
+pub fn render(input: &[(Xy, Xy)], area: &(Xy, Xy)) -> Result<String> {
+    let (min, max) = area;
+    let side = |lo: i64, hi: i64| hi.checked_sub(lo)?.checked_add(1);
+    let cells = side(min.x(), max.x())
+        .zip(side(min.y(), max.y()))
+        .and_then(|(w, h)| w.checked_mul(h))
+        .filter(|cells| *cells <= RENDER_MAX_CELLS)
+        .ok_or_else(|| {
+            eyre!(
+                "area from {:?} to {:?} is too large to render, the limit is {} cells",
+                min,
+                max,
+                RENDER_MAX_CELLS
+            )
+        })?;
+    let sensors = input.iter().copied().map(Sensor::from).collect::<Vec<_>>();
+    let sensor_xys = input.iter().map(|(s, _)| *s).collect::<HashSet<_>>();
+    let beacon_xys = input.iter().map(|(_, b)| *b).collect::<HashSet<_>>();
+    let mut map = String::with_capacity(cells as usize);
+    for y in min.y()..=max.y() {
+        for x in min.x()..=max.x() {
+            let xy = Xy::new([x, y]);
+            map.push(if sensor_xys.contains(&xy) {
+                'S'
+            } else if beacon_xys.contains(&xy) {
+                'B'
+            } else if sensors.iter().any(|s| s.covered(&xy)) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        map.push('\n');
+    }
+    Ok(map)
+}
+
+#[test]
+fn test_render() -> Result<()> {
+    let input = parser::parse(EXAMPLE.as_bytes())?;
+    assert_eq!(
+        render(&input, &((-3, 10).into(), (25, 10).into()))?,
+        format!(".####B{}.\n", "#".repeat(22))
+    );
+    let map = render(&input, &Config::EXAMPLE.area())?;
+    assert_eq!(map.lines().nth(11), Some("S#############.######"));
+    // The default area of the real input is far too large
+    assert!(render(&input, &Config::REAL.area()).is_err());
+    assert!(render(&input, &((i64::MIN, 0).into(), (i64::MAX, 0).into())).is_err());
+    Ok(())
 }
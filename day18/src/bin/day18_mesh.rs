@@ -0,0 +1,57 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use std::collections::HashSet;
+use std::env;
+use std::io::{stdin, BufRead};
+
+use day18::*;
+
+fn process(bufin: impl BufRead, format: mesh::Format, exterior_only: bool) -> Result<String> {
+    let input = parser::parse(bufin)?;
+    let cubes = input.into_iter().collect::<HashSet<Xyz>>();
+    let droplet = voxel::Droplet::new(&cubes)?;
+    Ok(mesh::export(&droplet, format, exterior_only))
+}
+
+const USAGE: &str = "usage: day18_mesh [obj|stl] [--exterior] < input";
+
+/// Parses the mesh format, obj by default, and the --exterior flag
+fn args(args: impl IntoIterator<Item = String>) -> Result<(mesh::Format, bool)> {
+    let mut format = None;
+    let mut exterior_only = false;
+    for arg in args {
+        match arg.as_str() {
+            "--exterior" if !exterior_only => exterior_only = true,
+            "obj" | "stl" if format.is_none() => format = Some(arg.parse()?),
+            _ => return Err(eyre!("unexpected argument {}\n{}", arg, USAGE)),
+        }
+    }
+    Ok((format.unwrap_or(mesh::Format::Obj), exterior_only))
+}
+
+#[test]
+fn test() -> Result<()> {
+    let obj = process(EXAMPLE.as_bytes(), mesh::Format::Obj, true)?;
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 58);
+    Ok(())
+}
+
+#[test]
+fn test_args() -> Result<()> {
+    let strings = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(args(vec![])?, (mesh::Format::Obj, false));
+    assert_eq!(args(strings("--exterior stl"))?, (mesh::Format::Stl, true));
+    assert!(args(strings("--exterior-only")).is_err());
+    assert!(args(strings("ply")).is_err());
+    assert!(args(strings("obj stl")).is_err());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let (format, exterior_only) = args(env::args().skip(1))?;
+    print!("{}", process(stdin().lock(), format, exterior_only)?);
+    Ok(())
+}
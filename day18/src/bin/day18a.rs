@@ -7,18 +7,10 @@ use std::io::{stdin, BufRead};
 
 use day18::*;
 
-fn process(bufin: impl BufRead) -> Result<i32> {
+fn process(bufin: impl BufRead) -> Result<usize> {
     let input = parser::parse(bufin)?;
     let cubes = input.into_iter().collect::<HashSet<Xyz>>();
-    let mut area = 0;
-    for xyz in &cubes {
-        for neigh in xyz.iter_neighs() {
-            if !cubes.contains(&neigh) {
-                area += 1;
-            }
-        }
-    }
-    Ok(area)
+    Ok(voxel::Droplet::new(&cubes)?.surface())
 }
 
 #[test]
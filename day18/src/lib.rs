@@ -2,8 +2,12 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
+pub use aoc::point::{bounding_box, Point3};
 pub use color_eyre::{eyre::eyre, Result};
-use std::ops;
+use std::collections::HashSet;
+
+pub mod mesh;
+pub mod voxel;
 
 pub const EXAMPLE: &str = "2,2,2
 1,2,2
@@ -20,31 +24,10 @@ pub const EXAMPLE: &str = "2,2,2
 2,3,5
 ";
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct Xyz(pub i32, pub i32, pub i32);
-
-impl Xyz {
-    pub fn iter_dirs() -> impl Iterator<Item = Xyz> {
-        vec![
-            Xyz(1, 0, 0),
-            Xyz(0, 1, 0),
-            Xyz(0, 0, 1),
-            Xyz(-1, 0, 0),
-            Xyz(0, -1, 0),
-            Xyz(0, 0, -1),
-        ]
-        .into_iter()
-    }
-    pub fn iter_neighs(&self) -> impl Iterator<Item = Xyz> + '_ {
-        Xyz::iter_dirs().map(|d| *self + d)
-    }
-}
+pub type Xyz = Point3<i32>;
 
-impl ops::Add for Xyz {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self::Output {
-        Xyz(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
-    }
+pub fn minmax_calc(cubes: &HashSet<Xyz>) -> Result<(Xyz, Xyz)> {
+    bounding_box(cubes).ok_or_else(|| eyre!("no cubes"))
 }
 
 pub mod parser {
@@ -59,7 +42,7 @@ pub mod parser {
         let (input, _) = bytes::tag(",")(input)?;
         let (input, z) = character::i32(input)?;
         let (input, _) = character::newline(input)?;
-        Ok((input, Xyz(x, y, z)))
+        Ok((input, Xyz::new([x, y, z])))
     }
 
     pub fn parse(mut bufin: impl BufRead) -> Result<Vec<Xyz>> {
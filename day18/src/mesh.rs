@@ -0,0 +1,162 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Export of droplet faces as Wavefront OBJ and ASCII STL meshes.
+//!
+//! Cube `Xyz::new([x, y, z])` occupies `[x, x+1]x[y, y+1]x[z, z+1]`, and each
+//! face `(cube, direction)` becomes a quad facing outwards.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Obj,
+    Stl,
+}
+
+impl std::str::FromStr for Format {
+    type Err = color_eyre::Report;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "obj" => Ok(Format::Obj),
+            "stl" => Ok(Format::Stl),
+            _ => Err(eyre!("unknown mesh format {}", s)),
+        }
+    }
+}
+
+/// Corners of the face, counter-clockwise when seen from outside
+pub fn face_quad(cube: &Xyz, dir: &Xyz) -> [Xyz; 4] {
+    let (c, d) = (cube.0, dir.0);
+    let a = d.iter().position(|v| *v != 0).unwrap();
+    let (u, v) = ((a + 1) % 3, (a + 2) % 3);
+    let corner = |du, dv| {
+        let mut p = c;
+        if d[a] > 0 {
+            p[a] += 1;
+        }
+        p[u] += du;
+        p[v] += dv;
+        Xyz::new(p)
+    };
+    let quad = [corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)];
+    if d[a] > 0 {
+        quad
+    } else {
+        [quad[0], quad[3], quad[2], quad[1]]
+    }
+}
+
+/// Wavefront OBJ with shared vertices and one quad per face
+pub fn to_obj(faces: impl Iterator<Item = (Xyz, Xyz)>) -> String {
+    let mut vertices = HashMap::<Xyz, usize>::new();
+    let mut vlines = String::new();
+    let mut flines = String::new();
+    for (cube, dir) in faces {
+        let ids = face_quad(&cube, &dir).map(|p| {
+            let next = vertices.len() + 1;
+            *vertices.entry(p).or_insert_with(|| {
+                writeln!(vlines, "v {} {} {}", p[0], p[1], p[2]).unwrap();
+                next
+            })
+        });
+        writeln!(flines, "f {} {} {} {}", ids[0], ids[1], ids[2], ids[3]).unwrap();
+    }
+    vlines + &flines
+}
+
+/// Coordinates as the floats STL expects, in scientific notation
+fn stl_coords(p: &Xyz) -> String {
+    p.0.map(|c| format!("{:.6e}", f64::from(c))).join(" ")
+}
+
+/// ASCII STL with two triangles per face
+pub fn to_stl(name: &str, faces: impl Iterator<Item = (Xyz, Xyz)>) -> String {
+    let mut stl = format!("solid {}\n", name);
+    for (cube, dir) in faces {
+        let q = face_quad(&cube, &dir);
+        for tri in [[q[0], q[1], q[2]], [q[0], q[2], q[3]]] {
+            writeln!(stl, "  facet normal {}", stl_coords(&dir)).unwrap();
+            writeln!(stl, "    outer loop").unwrap();
+            for p in tri {
+                writeln!(stl, "      vertex {}", stl_coords(&p)).unwrap();
+            }
+            writeln!(stl, "    endloop").unwrap();
+            writeln!(stl, "  endfacet").unwrap();
+        }
+    }
+    writeln!(stl, "endsolid {}", name).unwrap();
+    stl
+}
+
+pub fn export(droplet: &voxel::Droplet, format: Format, exterior_only: bool) -> String {
+    let faces = droplet.faces(exterior_only);
+    match format {
+        Format::Obj => to_obj(faces),
+        Format::Stl => to_stl("droplet", faces),
+    }
+}
+
+#[test]
+fn test_face_quad() {
+    // Normal of the quad must match the direction of the face
+    for dir in Xyz::iter_dirs() {
+        let q = face_quad(&Xyz::zero(), &dir);
+        let (e1, e2) = (q[1] - q[0], q[2] - q[1]);
+        let normal = Xyz::new([
+            e1[1] * e2[2] - e1[2] * e2[1],
+            e1[2] * e2[0] - e1[0] * e2[2],
+            e1[0] * e2[1] - e1[1] * e2[0],
+        ]);
+        assert_eq!(normal, dir);
+    }
+}
+
+#[test]
+fn test_export() -> Result<()> {
+    let cubes = parser::parse(EXAMPLE.as_bytes())?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let droplet = voxel::Droplet::new(&cubes)?;
+    let count = |mesh: String, prefix: &str| mesh.lines().filter(|l| l.starts_with(prefix)).count();
+    assert_eq!(count(export(&droplet, Format::Obj, false), "f "), 64);
+    assert_eq!(count(export(&droplet, Format::Obj, true), "f "), 58);
+    assert_eq!(
+        count(export(&droplet, Format::Stl, false), "  facet "),
+        2 * 64
+    );
+    assert_eq!(
+        count(export(&droplet, Format::Stl, true), "  facet "),
+        2 * 58
+    );
+    Ok(())
+}
+
+#[test]
+fn test_stl() {
+    let stl = to_stl("cube", [(Xyz::zero(), Xyz::new([0, 0, -1]))].into_iter());
+    assert_eq!(
+        stl,
+        "solid cube
+  facet normal 0.000000e0 0.000000e0 -1.000000e0
+    outer loop
+      vertex 0.000000e0 0.000000e0 0.000000e0
+      vertex 0.000000e0 1.000000e0 0.000000e0
+      vertex 1.000000e0 1.000000e0 0.000000e0
+    endloop
+  endfacet
+  facet normal 0.000000e0 0.000000e0 -1.000000e0
+    outer loop
+      vertex 0.000000e0 0.000000e0 0.000000e0
+      vertex 1.000000e0 1.000000e0 0.000000e0
+      vertex 1.000000e0 0.000000e0 0.000000e0
+    endloop
+  endfacet
+endsolid cube
+"
+    );
+}
@@ -0,0 +1,270 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Dense 3D bitsets over the bounding box of the droplet.
+
+use std::collections::HashSet;
+
+use super::*;
+
+/// Most positions a [`Voxels`] box can have, 16MiB of bits
+pub const MAX_VOXELS: usize = 1 << 27;
+
+/// Dense 3D bitset over an inclusive bounding box
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Voxels {
+    pub min: Xyz,
+    pub max: Xyz,
+    bits: Vec<u64>,
+}
+
+impl Voxels {
+    /// Creates an empty set over the box, failing if it's empty or has
+    /// more than [`MAX_VOXELS`] positions
+    pub fn new(min: Xyz, max: Xyz) -> Result<Voxels> {
+        let len = (0..3)
+            .map(|i| usize::try_from(i64::from(max[i]) - i64::from(min[i]) + 1).ok())
+            .try_fold(1_usize, |acc, side| acc.checked_mul(side?))
+            .filter(|len| (1..=MAX_VOXELS).contains(len))
+            .ok_or_else(|| eyre!("invalid voxel box from {:?} to {:?}", min, max))?;
+        Ok(Voxels {
+            min,
+            max,
+            bits: vec![0; len.div_ceil(64)],
+        })
+    }
+    /// Empty set over the same box
+    fn cleared(&self) -> Voxels {
+        Voxels {
+            min: self.min,
+            max: self.max,
+            bits: vec![0; self.bits.len()],
+        }
+    }
+    fn index(&self, xyz: &Xyz) -> Option<usize> {
+        if !xyz.is_inside(&(self.min, self.max)) {
+            return None;
+        }
+        // Offsets and sides as usize, which Voxels::new checked
+        let offset = |i: usize| (i64::from(xyz[i]) - i64::from(self.min[i])) as usize;
+        let side = |i: usize| (i64::from(self.max[i]) - i64::from(self.min[i]) + 1) as usize;
+        Some(offset(0) + side(0) * (offset(1) + side(1) * offset(2)))
+    }
+    pub fn contains(&self, xyz: &Xyz) -> bool {
+        self.index(xyz)
+            .is_some_and(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+    /// Inserts `xyz` in the set; returns false if it's out of bounds
+    pub fn insert(&mut self, xyz: &Xyz) -> bool {
+        if let Some(i) = self.index(xyz) {
+            self.bits[i / 64] |= 1 << (i % 64);
+            true
+        } else {
+            false
+        }
+    }
+    /// Iterates over all positions of the bounding box
+    pub fn iter_box(&self) -> impl Iterator<Item = Xyz> + '_ {
+        (self.min.z()..=self.max.z()).flat_map(move |z| {
+            (self.min.y()..=self.max.y())
+                .flat_map(move |y| (self.min.x()..=self.max.x()).map(move |x| Xyz::new([x, y, z])))
+        })
+    }
+    /// Iterates over the positions in the set
+    pub fn iter(&self) -> impl Iterator<Item = Xyz> + '_ {
+        self.iter_box().filter(|xyz| self.contains(xyz))
+    }
+    /// Positions of the box connected to `start` without crossing `self`
+    pub fn flood(&self, start: &Xyz) -> Voxels {
+        let mut filled = self.cleared();
+        self.flood_into(start, &mut filled);
+        filled
+    }
+    /// Inserts in `filled` the positions connected to `start` without
+    /// crossing `self` or what's already in `filled`, returning them
+    fn flood_into(&self, start: &Xyz, filled: &mut Voxels) -> Vec<Xyz> {
+        if self.contains(start) || filled.contains(start) || !filled.insert(start) {
+            return vec![];
+        }
+        let mut cells = vec![*start];
+        let mut front = vec![*start];
+        while let Some(xyz) = front.pop() {
+            for neigh in xyz.iter_neighs() {
+                if !self.contains(&neigh) && !filled.contains(&neigh) && filled.insert(&neigh) {
+                    cells.push(neigh);
+                    front.push(neigh);
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// Lava droplet with the air around it
+#[derive(Debug, Clone)]
+pub struct Droplet {
+    pub lava: Voxels,
+    /// Air that can reach the outside of the droplet
+    pub exterior: Voxels,
+}
+
+impl Droplet {
+    /// Creates the droplet in its bounding box padded by one, so that
+    /// the exterior air surrounds it completely
+    ///
+    /// Without cubes, the droplet is empty and all air is exterior.
+    /// Cubes must be at least 2 away from the limits of `i32`, so that
+    /// the padding and its neighbours have coordinates.
+    pub fn new(cubes: &HashSet<Xyz>) -> Result<Droplet> {
+        let (min, max) = if cubes.is_empty() {
+            (Xyz::zero(), Xyz::zero())
+        } else {
+            minmax_calc(cubes)?
+        };
+        if (0..3).any(|i| min[i] < i32::MIN + 2 || max[i] > i32::MAX - 2) {
+            return Err(eyre!(
+                "cubes from {:?} to {:?} are too close to the coordinate limits",
+                min,
+                max
+            ));
+        }
+        let mut lava = Voxels::new(min - Xyz::new([1, 1, 1]), max + Xyz::new([1, 1, 1]))?;
+        for xyz in cubes {
+            lava.insert(xyz);
+        }
+        let exterior = lava.flood(&lava.min);
+        Ok(Droplet { lava, exterior })
+    }
+    /// Lava faces as `(cube, direction)`; the faces touching other
+    /// lava are skipped, and with `exterior_only` also the ones touching
+    /// trapped air
+    pub fn faces(&self, exterior_only: bool) -> impl Iterator<Item = (Xyz, Xyz)> + '_ {
+        self.lava
+            .iter()
+            .flat_map(|xyz| Xyz::iter_dirs().map(move |d| (xyz, d)))
+            .filter(move |(xyz, d)| {
+                let neigh = *xyz + *d;
+                if exterior_only {
+                    self.exterior.contains(&neigh)
+                } else {
+                    !self.lava.contains(&neigh)
+                }
+            })
+    }
+    /// Number of lava faces that are not touching other lava
+    pub fn surface(&self) -> usize {
+        self.faces(false).count()
+    }
+    /// Number of lava faces touching exterior air
+    pub fn exterior_surface(&self) -> usize {
+        self.faces(true).count()
+    }
+    /// Air pockets trapped inside the droplet, as connected components
+    /// with their cells in the order of [`Voxels::iter_box`]
+    pub fn cavities(&self) -> Vec<Vec<Xyz>> {
+        // Starting with the exterior, so that the floods only fill the
+        // cavities, each of them once
+        let mut seen = self.exterior.clone();
+        let mut cavities = vec![];
+        for xyz in self.lava.iter_box() {
+            let mut cells = self.lava.flood_into(&xyz, &mut seen);
+            if !cells.is_empty() {
+                cells.sort_by_key(|c| (c.z(), c.y(), c.x()));
+                cavities.push(cells);
+            }
+        }
+        cavities
+    }
+}
+
+#[test]
+fn test_example() -> Result<()> {
+    let cubes = parser::parse(EXAMPLE.as_bytes())?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let droplet = Droplet::new(&cubes)?;
+    assert_eq!(droplet.lava.iter().count(), 13);
+    assert_eq!(droplet.surface(), 64);
+    assert_eq!(droplet.exterior_surface(), 58);
+    assert_eq!(droplet.cavities(), vec![vec![Xyz::new([2, 2, 5])]]);
+    Ok(())
+}
+
+#[test]
+fn test_empty() -> Result<()> {
+    let droplet = Droplet::new(&HashSet::new())?;
+    assert_eq!(droplet.surface(), 0);
+    assert_eq!(droplet.exterior_surface(), 0);
+    assert!(droplet.cavities().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_limits() -> Result<()> {
+    let cubes = [Xyz::new([i32::MAX, 0, 0])].into_iter().collect();
+    assert!(Droplet::new(&cubes).is_err());
+    let cubes = [Xyz::new([0, i32::MIN + 1, 0])].into_iter().collect();
+    assert!(Droplet::new(&cubes).is_err());
+    let cubes = [Xyz::new([0, 0, i32::MAX - 2])].into_iter().collect();
+    assert_eq!(Droplet::new(&cubes)?.exterior_surface(), 6);
+    // Far apart cubes make a box too large to fill
+    let cubes = [Xyz::new([-100000, 0, 0]), Xyz::new([100000, 0, 10000])]
+        .into_iter()
+        .collect();
+    assert!(Droplet::new(&cubes).is_err());
+    assert!(Voxels::new(Xyz::new([i32::MIN; 3]), Xyz::new([i32::MAX; 3])).is_err());
+    assert!(Voxels::new(Xyz::new([1, 0, 0]), Xyz::zero()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_many_cavities() -> Result<()> {
+    // Lattice of lava with a one-cell pocket at each odd position
+    let side = 41;
+    let cubes = (0..side)
+        .flat_map(|z| (0..side).flat_map(move |y| (0..side).map(move |x| Xyz::new([x, y, z]))))
+        .filter(|c| (0..3).any(|i| c[i] % 2 == 0))
+        .collect::<HashSet<_>>();
+    let droplet = Droplet::new(&cubes)?;
+    let cavities = droplet.cavities();
+    assert_eq!(cavities.len(), 20 * 20 * 20);
+    assert!(cavities.iter().all(|c| c.len() == 1));
+    Ok(())
+}
+
+#[test]
+fn test_cavities() -> Result<()> {
+    // 4x3x3 block with a 2-cube hole in the middle, and a 1-cube hole
+    // in a separate 3x3x3 block
+    let mut cubes = HashSet::<Xyz>::new();
+    for x in 0..4 {
+        for y in 0..3 {
+            for z in 0..3 {
+                if !(y == 1 && z == 1 && (x == 1 || x == 2)) {
+                    cubes.insert(Xyz::new([x, y, z]));
+                }
+            }
+        }
+    }
+    for x in 10..13 {
+        for y in 0..3 {
+            for z in 0..3 {
+                if (x, y, z) != (11, 1, 1) {
+                    cubes.insert(Xyz::new([x, y, z]));
+                }
+            }
+        }
+    }
+    let droplet = Droplet::new(&cubes)?;
+    let mut volumes = droplet
+        .cavities()
+        .iter()
+        .map(|c| c.len())
+        .collect::<Vec<_>>();
+    volumes.sort();
+    assert_eq!(volumes, vec![1, 2]);
+    assert_eq!(droplet.exterior_surface(), 2 * (12 + 12 + 9) + 6 * 9);
+    assert_eq!(droplet.surface(), droplet.exterior_surface() + 10 + 6);
+    Ok(())
+}
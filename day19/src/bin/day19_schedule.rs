@@ -0,0 +1,40 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use std::env;
+use std::io::{stdin, BufRead};
+
+use day19::*;
+
+fn process(bufin: impl BufRead, minutes: i32) -> Result<Vec<Schedule>> {
+    let factories = parser::parse(bufin)?;
+    factories
+        .iter()
+        .map(|factory| Ok(Schedule::new(factory, factory.resource("geode")?, minutes)))
+        .collect()
+}
+
+#[test]
+fn test() -> Result<()> {
+    let schedules = process(EXAMPLE.as_bytes(), 24)?;
+    assert_eq!(
+        schedules.iter().map(|s| s.output()).collect::<Vec<_>>(),
+        vec![9, 12]
+    );
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let minutes = match env::args().nth(1) {
+        Some(arg) => arg.parse()?,
+        None => 24,
+    };
+    for schedule in process(stdin().lock(), minutes)? {
+        println!("Blueprint {}:\n", schedule.id);
+        println!("{}", schedule);
+        println!("Geodes: {}\n", schedule.output());
+    }
+    Ok(())
+}
@@ -3,202 +3,239 @@
 // file 'LICENSE', which is part of this source code package.
 
 pub use color_eyre::{eyre::eyre, Result};
-use std::cmp;
-use std::cmp::Ordering;
-use std::ops;
+use std::fmt;
+
+pub mod recipe;
+pub use recipe::{parser, Build, Factory, Recipe, Resource};
 
 pub const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
 ";
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Mats(pub [i32; 4]);
+/* Build schedule */
+
+/// What happened in a single minute of a [`Schedule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub minute: i32,
+    /// Robot ordered in this minute, with what it cost
+    pub build: Option<Recipe>,
+    /// Materials at the end of the minute, indexed by resource
+    pub mats: Vec<i32>,
+    /// Robots at the end of the minute, indexed by resource
+    pub robots: Vec<i32>,
+}
+
+/// The optimal build order for a blueprint, minute by minute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub id: i32,
+    pub resources: Vec<String>,
+    /// Robots we start with, indexed by resource
+    pub start: Vec<i32>,
+    /// Resource the schedule maximizes
+    pub target: Resource,
+    pub builds: Vec<Build>,
+    pub steps: Vec<Step>,
+}
 
-impl Mats {
-    pub const ORE: Mats = Mats::const_mats::<1, 0, 0, 0>();
-    pub const CLAY: Mats = Mats::const_mats::<0, 1, 0, 0>();
-    pub const OBS: Mats = Mats::const_mats::<0, 0, 1, 0>();
-    pub const GEODE: Mats = Mats::const_mats::<0, 0, 0, 1>();
-    pub fn new(ore: i32, clay: i32, obs: i32, geode: i32) -> Mats {
-        Mats([ore, clay, obs, geode])
+impl Schedule {
+    pub fn new(factory: &Factory, target: Resource, minutes: i32) -> Schedule {
+        let (_, builds) = factory.best_builds(target, minutes);
+        Schedule::from_builds(factory, target, minutes, builds)
     }
-    pub const fn const_mats<const ORE: i32, const CLAY: i32, const OBS: i32, const GEODE: i32>(
-    ) -> Mats {
-        Mats([ORE, CLAY, OBS, GEODE])
+    fn from_builds(
+        factory: &Factory,
+        target: Resource,
+        minutes: i32,
+        builds: Vec<Build>,
+    ) -> Schedule {
+        let mut mats = vec![0; factory.resources.len()];
+        let mut robots = factory.start.clone();
+        let mut pending = builds.iter().peekable();
+        let mut steps = vec![];
+        for minute in 1..=minutes {
+            let build = pending
+                .next_if(|(m, _)| *m == minute)
+                .map(|(_, irecipe)| factory.recipes[*irecipe].clone());
+            for (mat, robot) in mats.iter_mut().zip(&robots) {
+                *mat += robot;
+            }
+            if let Some(recipe) = &build {
+                for (mat, cost) in mats.iter_mut().zip(&recipe.cost) {
+                    *mat -= cost;
+                }
+                robots[recipe.robot] += 1;
+            }
+            steps.push(Step {
+                minute,
+                build,
+                mats: mats.clone(),
+                robots: robots.clone(),
+            });
+        }
+        Schedule {
+            id: factory.id,
+            resources: factory.resources.clone(),
+            start: factory.start.clone(),
+            target,
+            builds,
+            steps,
+        }
     }
-    pub fn geode(&self) -> i32 {
-        self.0[3]
+    /// Amount of the target resource at the end
+    pub fn output(&self) -> i32 {
+        self.steps.last().map_or(0, |s| s.mats[self.target])
     }
-}
-
-impl ops::Add for Mats {
-    type Output = Self;
-    fn add(self, other: Self) -> Self {
-        Mats([
-            self.0[0] + other.0[0],
-            self.0[1] + other.0[1],
-            self.0[2] + other.0[2],
-            self.0[3] + other.0[3],
-        ])
+    fn robot_name(&self, resource: Resource) -> String {
+        match self.resources[resource].as_str() {
+            "geode" => "geode-cracking".to_string(),
+            name => format!("{}-collecting", name),
+        }
     }
-}
-
-impl ops::Sub for Mats {
-    type Output = Self;
-    fn sub(self, other: Self) -> Self::Output {
-        Mats([
-            self.0[0] - other.0[0],
-            self.0[1] - other.0[1],
-            self.0[2] - other.0[2],
-            self.0[3] - other.0[3],
-        ])
+    fn mats_list(&self, mats: &[i32]) -> String {
+        mats.iter()
+            .zip(&self.resources)
+            .filter(|(qty, _)| **qty > 0)
+            .map(|(qty, name)| format!("{} {}", qty, name))
+            .collect::<Vec<_>>()
+            .join(" and ")
     }
 }
 
-impl cmp::PartialOrd for Mats {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.0
-            .iter()
-            .zip(other.0.iter())
-            .map(|(a, b)| a.partial_cmp(b))
-            .reduce(|prev, current| {
-                if current == Some(Ordering::Equal) || current == prev {
-                    prev
-                } else if prev == Some(Ordering::Equal) {
-                    current
+impl fmt::Display for Schedule {
+    /// Formats the schedule like the puzzle's minute-by-minute narration
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut prev = &self.start;
+        for step in &self.steps {
+            if step.minute > 1 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== Minute {} ==", step.minute)?;
+            if let Some(recipe) = &step.build {
+                let name = self.robot_name(recipe.robot);
+                let article = if name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                    "an"
                 } else {
-                    None
+                    "a"
+                };
+                writeln!(
+                    f,
+                    "Spend {} to start building {} {} robot.",
+                    self.mats_list(&recipe.cost),
+                    article,
+                    name
+                )?;
+            }
+            for (i, resource) in self.resources.iter().enumerate() {
+                let (num, have) = (prev[i], step.mats[i]);
+                if num == 0 {
+                    continue;
                 }
-            })
-            .unwrap()
-    }
-}
-
-#[test]
-fn test_ord() {
-    assert_eq!(Mats::ORE.partial_cmp(&Mats::CLAY), None);
-    assert_eq!(
-        Mats::ORE.partial_cmp(&(Mats::ORE + Mats::ORE)),
-        Some(Ordering::Less)
-    );
-    assert_eq!(
-        (Mats::ORE + Mats::ORE + Mats::CLAY).partial_cmp(&Mats::ORE),
-        Some(Ordering::Greater)
-    );
-    assert_eq!(Mats::GEODE.partial_cmp(&Mats::GEODE), Some(Ordering::Equal));
-}
-
-#[derive(Debug)]
-pub struct Blueprint {
-    pub id: i32,
-    pub ore_cost: Mats,
-    pub clay_cost: Mats,
-    pub obs_cost: Mats,
-    pub geode_cost: Mats,
-    pub max_cost: Mats,
-}
-
-impl Blueprint {
-    pub fn new(
-        id: i32,
-        ore_cost: Mats,
-        clay_cost: Mats,
-        obs_cost: Mats,
-        geode_cost: Mats,
-    ) -> Blueprint {
-        let mut bp = Blueprint {
-            id,
-            ore_cost,
-            clay_cost,
-            obs_cost,
-            geode_cost,
-            max_cost: Mats::default(),
-        };
-        bp.max_cost = bp._max_cost();
-        bp
-    }
-    fn _max_cost(&self) -> Mats {
-        let costs = [
-            self.ore_cost,
-            self.clay_cost,
-            self.obs_cost,
-            self.geode_cost,
-        ];
-        Mats::new(
-            costs.map(|c| c.0[0]).into_iter().max().unwrap(),
-            costs.map(|c| c.0[1]).into_iter().max().unwrap(),
-            costs.map(|c| c.0[2]).into_iter().max().unwrap(),
-            costs.map(|c| c.0[3]).into_iter().max().unwrap(),
-        )
-    }
-    pub fn robot_cost(&self, robot: &Robot) -> Mats {
-        match robot {
-            Robot::Ore => self.ore_cost,
-            Robot::Clay => self.clay_cost,
-            Robot::Obs => self.obs_cost,
-            Robot::Geode => self.geode_cost,
+                let s = if num == 1 { "" } else { "s" };
+                if resource == "geode" {
+                    let hs = if have == 1 { "" } else { "s" };
+                    writeln!(
+                        f,
+                        "{} {} robot{} crack{} {} geode{}; you now have {} open geode{}.",
+                        num,
+                        self.robot_name(i),
+                        s,
+                        if num == 1 { "s" } else { "" },
+                        num,
+                        s,
+                        have,
+                        hs
+                    )?;
+                } else {
+                    writeln!(
+                        f,
+                        "{} {} robot{} collect{} {} {}; you now have {} {}.",
+                        num,
+                        self.robot_name(i),
+                        s,
+                        if num == 1 { "s" } else { "" },
+                        num,
+                        resource,
+                        have,
+                        resource
+                    )?;
+                }
+            }
+            if let Some(recipe) = &step.build {
+                writeln!(
+                    f,
+                    "The new {} robot is ready; you now have {} of them.",
+                    self.robot_name(recipe.robot),
+                    step.robots[recipe.robot]
+                )?;
+            }
+            prev = &step.robots;
         }
+        Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum Robot {
-    Ore = 0,
-    Clay = 1,
-    Obs = 2,
-    Geode = 3,
+#[test]
+fn test() -> Result<()> {
+    assert_eq!(parser::parse(EXAMPLE.as_bytes())?.len(), 2);
+    Ok(())
 }
 
-impl Robot {
-    pub const ROBOTS: [Robot; 4] = [Robot::Ore, Robot::Clay, Robot::Obs, Robot::Geode];
-    pub fn produces(&self) -> Mats {
-        match self {
-            Robot::Ore => Mats::ORE,
-            Robot::Clay => Mats::CLAY,
-            Robot::Obs => Mats::OBS,
-            Robot::Geode => Mats::GEODE,
+#[test]
+fn test_schedule() -> Result<()> {
+    let factories = parser::parse(EXAMPLE.as_bytes())?;
+    let geode = factories[0].resource("geode")?;
+    let schedule1 = Schedule::new(&factories[0], geode, 24);
+    assert_eq!(schedule1.output(), 9);
+    let schedule2 = Schedule::new(&factories[1], geode, 24);
+    assert_eq!(schedule2.output(), 12);
+    for schedule in [&schedule1, &schedule2] {
+        // Every ordered robot must have been affordable
+        let mut mats = vec![0; schedule.resources.len()];
+        for step in &schedule.steps {
+            if let Some(recipe) = &step.build {
+                assert!(recipe.cost.iter().zip(&mats).all(|(cost, mat)| cost <= mat));
+            }
+            mats = step.mats.clone();
         }
     }
+    Ok(())
 }
 
-pub mod parser {
-    use aoc::parser::*;
-
-    use super::*;
-
-    fn line(input: &str) -> IResult<&str, Blueprint> {
-        let (input, _) = bytes::tag("Blueprint ")(input)?;
-        let (input, id) = character::i32(input)?;
-        let (input, _) = bytes::tag(": Each ore robot costs ")(input)?;
-        let (input, ore_ore) = character::i32(input)?;
-        let ore_cost = Mats::new(ore_ore, 0, 0, 0);
-        let (input, _) = bytes::tag(" ore. Each clay robot costs ")(input)?;
-        let (input, clay_ore) = character::i32(input)?;
-        let clay_cost = Mats::new(clay_ore, 0, 0, 0);
-        let (input, _) = bytes::tag(" ore. Each obsidian robot costs ")(input)?;
-        let (input, obs_ore) = character::i32(input)?;
-        let (input, _) = bytes::tag(" ore and ")(input)?;
-        let (input, obs_clay) = character::i32(input)?;
-        let obs_cost = Mats::new(obs_ore, obs_clay, 0, 0);
-        let (input, _) = bytes::tag(" clay. Each geode robot costs ")(input)?;
-        let (input, geode_ore) = character::i32(input)?;
-        let (input, _) = bytes::tag(" ore and ")(input)?;
-        let (input, geode_obs) = character::i32(input)?;
-        let geode_cost = Mats::new(geode_ore, 0, geode_obs, 0);
-        let (input, _) = bytes::tag(" obsidian.\n")(input)?;
-        Ok((
-            input,
-            Blueprint::new(id, ore_cost, clay_cost, obs_cost, geode_cost),
-        ))
-    }
-
-    pub fn parse(mut bufin: impl BufRead) -> Result<Vec<Blueprint>> {
-        aoc::parse_with!(multi::many1(line), bufin)
-    }
+#[test]
+fn test_schedule_narration() -> Result<()> {
+    let factories = parser::parse(EXAMPLE.as_bytes())?;
+    let geode = factories[0].resource("geode")?;
+    let text = Schedule::new(&factories[0], geode, 24).to_string();
+    assert!(text.starts_with(
+        "== Minute 1 ==
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+
+== Minute 2 ==
+1 ore-collecting robot collects 1 ore; you now have 2 ore.
+"
+    ));
+    assert!(text.contains(
+        "== Minute 11 ==
+Spend 3 ore and 14 clay to start building an obsidian-collecting robot.
+"
+    ));
+    assert!(text.ends_with("you now have 9 open geodes.\n"));
+    Ok(())
 }
 
 #[test]
-fn test() -> Result<()> {
-    assert_eq!(parser::parse(EXAMPLE.as_bytes())?.len(), 2);
+fn test_schedule_narration_ore() -> Result<()> {
+    let factories = parser::parse(EXAMPLE.as_bytes())?;
+    let geode = factories[0].resource("geode")?;
+    let text = Schedule::from_builds(&factories[0], geode, 5, vec![(5, 0)]).to_string();
+    assert!(text.ends_with(
+        "== Minute 5 ==
+Spend 4 ore to start building an ore-collecting robot.
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+The new ore-collecting robot is ready; you now have 2 of them.
+"
+    ));
     Ok(())
 }
@@ -0,0 +1,287 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Generic resource/recipe model: any number of named resources, with
+//! robots that cost any combination of them.
+
+use color_eyre::{eyre::eyre, Result};
+use std::cmp;
+
+/// Index of a resource in [`Factory::resources`]
+pub type Resource = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    /// Resource collected by the robot
+    pub robot: Resource,
+    /// Cost of the robot, indexed by resource
+    pub cost: Vec<i32>,
+}
+
+/// Robot name and cost as read from the blueprint text
+pub type NamedRecipe = (String, Vec<(i32, String)>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factory {
+    pub id: i32,
+    pub resources: Vec<String>,
+    pub recipes: Vec<Recipe>,
+    /// Robots we start with, indexed by resource
+    pub start: Vec<i32>,
+    /// Maximum cost of any recipe, indexed by resource
+    pub max_cost: Vec<i32>,
+}
+
+impl Factory {
+    /// Creates the factory from named recipes
+    ///
+    /// Resources are numbered in order of appearance, robots first; we
+    /// start with a single robot of the first recipe.
+    pub fn new(id: i32, named: &[NamedRecipe]) -> Result<Factory> {
+        let mut resources = Vec::<String>::new();
+        let mut index = |name: &str| {
+            resources.iter().position(|r| r == name).unwrap_or_else(|| {
+                resources.push(name.to_string());
+                resources.len() - 1
+            })
+        };
+        for (robot, _) in named {
+            index(robot);
+        }
+        let named = named
+            .iter()
+            .map(|(robot, cost)| {
+                let cost = cost
+                    .iter()
+                    .map(|(qty, name)| (*qty, index(name)))
+                    .collect::<Vec<_>>();
+                (index(robot), cost)
+            })
+            .collect::<Vec<_>>();
+        let nresources = resources.len();
+        let mut recipes = Vec::<Recipe>::new();
+        for (robot, cost) in named {
+            if recipes.iter().any(|r| r.robot == robot) {
+                return Err(eyre!(
+                    "blueprint {} has more than one {} robot",
+                    id,
+                    resources[robot]
+                ));
+            }
+            let mut costv = vec![0; nresources];
+            for (qty, resource) in cost {
+                costv[resource] += qty;
+            }
+            recipes.push(Recipe { robot, cost: costv });
+        }
+        let first = recipes
+            .first()
+            .ok_or_else(|| eyre!("blueprint {} has no recipes", id))?
+            .robot;
+        let mut start = vec![0; nresources];
+        start[first] = 1;
+        let max_cost = (0..nresources)
+            .map(|i| recipes.iter().map(|r| r.cost[i]).max().unwrap_or(0))
+            .collect();
+        Ok(Factory {
+            id,
+            resources,
+            recipes,
+            start,
+            max_cost,
+        })
+    }
+
+    pub fn resource(&self, name: &str) -> Result<Resource> {
+        self.resources
+            .iter()
+            .position(|r| r == name)
+            .ok_or_else(|| eyre!("resource {} not found in blueprint {}", name, self.id))
+    }
+
+    /// Maximum amount of `target` that can be collected in `minutes`
+    pub fn max_output(&self, target: Resource, minutes: i32) -> i32 {
+        self.best_builds(target, minutes).0
+    }
+
+    /// Build order that collects the most `target` in `minutes`, and how
+    /// much it collects
+    pub fn best_builds(&self, target: Resource, minutes: i32) -> (i32, Vec<Build>) {
+        let mut search = Search {
+            factory: self,
+            target,
+            minutes,
+            builds: vec![],
+            best: (-1, vec![]),
+        };
+        search.run(&vec![0; self.resources.len()], &self.start, minutes);
+        search.best
+    }
+}
+
+/// Robot ordered at a minute, as the index of its recipe
+pub type Build = (i32, usize);
+
+/// Depth-first search over the next robot to build
+struct Search<'a> {
+    factory: &'a Factory,
+    target: Resource,
+    minutes: i32,
+    /// Builds of the current branch
+    builds: Vec<Build>,
+    best: (i32, Vec<Build>),
+}
+
+impl Search<'_> {
+    fn run(&mut self, mats: &[i32], robots: &[i32], left: i32) {
+        let target = self.target;
+        // Amount we get if we stop building now:
+        let idle = mats[target] + robots[target] * left;
+        if idle > self.best.0 {
+            self.best = (idle, self.builds.clone());
+        }
+        // Upper bound: a new target robot every remaining minute
+        if idle + left * (left - 1) / 2 <= self.best.0 {
+            return;
+        }
+        let factory = self.factory;
+        for (irecipe, recipe) in factory.recipes.iter().enumerate().rev() {
+            if recipe.robot != target && robots[recipe.robot] >= factory.max_cost[recipe.robot] {
+                // Can't spend more than this per minute anyway
+                continue;
+            }
+            // Minutes we have to wait for the materials:
+            let wait = (0..mats.len())
+                .map(|i| {
+                    let missing = recipe.cost[i] - mats[i];
+                    if missing <= 0 {
+                        Some(0)
+                    } else if robots[i] == 0 {
+                        None
+                    } else {
+                        Some((missing + robots[i] - 1) / robots[i])
+                    }
+                })
+                .try_fold(0, |acc, w| w.map(|w| cmp::max(acc, w)));
+            let Some(wait) = wait else {
+                continue;
+            };
+            if wait + 1 >= left {
+                // Robot would be ready too late to produce anything
+                continue;
+            }
+            let next_mats = (0..mats.len())
+                .map(|i| mats[i] + robots[i] * (wait + 1) - recipe.cost[i])
+                .collect::<Vec<_>>();
+            let mut next_robots = robots.to_vec();
+            next_robots[recipe.robot] += 1;
+            self.builds.push((self.minutes - left + 1 + wait, irecipe));
+            self.run(&next_mats, &next_robots, left - wait - 1);
+            self.builds.pop();
+        }
+    }
+}
+
+pub mod parser {
+    use aoc::parser::*;
+
+    use super::*;
+
+    fn name(input: &str) -> IResult<&str, String> {
+        combinator::map(character::alpha1, String::from)(input)
+    }
+
+    fn cost_item(input: &str) -> IResult<&str, (i32, String)> {
+        let (input, qty) = character::i32(input)?;
+        let (input, _) = character::space1(input)?;
+        let (input, name) = name(input)?;
+        Ok((input, (qty, name)))
+    }
+
+    fn cost(input: &str) -> IResult<&str, Vec<(i32, String)>> {
+        branch::alt((
+            combinator::value(vec![], bytes::tag("nothing")),
+            multi::separated_list1(
+                branch::alt((bytes::tag(", and "), bytes::tag(", "), bytes::tag(" and "))),
+                cost_item,
+            ),
+        ))(input)
+    }
+
+    fn recipe(input: &str) -> IResult<&str, NamedRecipe> {
+        let (input, _) = character::multispace1(input)?;
+        let (input, _) = bytes::tag("Each ")(input)?;
+        let (input, robot) = name(input)?;
+        let (input, _) = bytes::tag(" robot costs ")(input)?;
+        let (input, cost) = cost(input)?;
+        let (input, _) = bytes::tag(".")(input)?;
+        Ok((input, (robot, cost)))
+    }
+
+    fn blueprint(input: &str) -> IResult<&str, (i32, Vec<NamedRecipe>)> {
+        let (input, _) = character::multispace0(input)?;
+        let (input, _) = bytes::tag("Blueprint ")(input)?;
+        let (input, id) = character::i32(input)?;
+        let (input, _) = bytes::tag(":")(input)?;
+        let (input, recipes) = multi::many1(recipe)(input)?;
+        let (input, _) = character::multispace0(input)?;
+        Ok((input, (id, recipes)))
+    }
+
+    pub fn parse(mut bufin: impl BufRead) -> Result<Vec<Factory>> {
+        let blueprints: Result<Vec<_>> = aoc::parse_with!(multi::many1(blueprint), bufin);
+        blueprints?
+            .iter()
+            .map(|(id, recipes)| Factory::new(*id, recipes))
+            .collect()
+    }
+}
+
+#[test]
+fn test_example() -> Result<()> {
+    let factories = parser::parse(crate::EXAMPLE.as_bytes())?;
+    assert_eq!(factories.len(), 2);
+    let factory = &factories[0];
+    assert_eq!(factory.id, 1);
+    assert_eq!(factory.resources, vec!["ore", "clay", "obsidian", "geode"]);
+    assert_eq!(factory.start, vec![1, 0, 0, 0]);
+    assert_eq!(
+        factory
+            .recipes
+            .iter()
+            .map(|r| (r.robot, r.cost.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, vec![4, 0, 0, 0]),
+            (1, vec![2, 0, 0, 0]),
+            (2, vec![3, 14, 0, 0]),
+            (3, vec![2, 0, 7, 0]),
+        ]
+    );
+    assert_eq!(factory.max_cost, vec![4, 14, 7, 0]);
+    let geode = factories[0].resource("geode")?;
+    assert_eq!(factories[0].max_output(geode, 24), 9);
+    assert_eq!(factories[1].max_output(geode, 24), 12);
+    Ok(())
+}
+
+#[test]
+fn test_custom() -> Result<()> {
+    let input = "Blueprint 7:
+  Each wood robot costs 2 wood.
+  Each stone robot costs 1 wood, 1 water and 2 wood.
+  Each water robot costs nothing.
+  Each gold robot costs 3 stone and 2 water.
+";
+    let factories = parser::parse(input.as_bytes())?;
+    let factory = &factories[0];
+    assert_eq!(factory.id, 7);
+    assert_eq!(factory.resources, vec!["wood", "stone", "water", "gold"]);
+    assert_eq!(factory.recipes[1].cost, vec![3, 0, 1, 0]);
+    assert_eq!(factory.recipes[2].cost, vec![0, 0, 0, 0]);
+    assert_eq!(factory.max_cost, vec![3, 3, 2, 0]);
+    assert!(factory.resource("diamond").is_err());
+    assert!(factory.max_output(factory.resource("gold")?, 10) > 0);
+    Ok(())
+}
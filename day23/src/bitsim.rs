@@ -0,0 +1,191 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Bit-parallel simulation: elves are stored as rows of bits, and the
+//! proposals of whole rows are computed with shifts and masks.
+//!
+//! Only the puzzle rules are supported.
+
+use super::*;
+
+/// Free space around the elves when the rows are laid out
+const MARGIN: i32 = 32;
+
+type Row = Vec<u64>;
+
+/// Word `i` of row `y`, or 0 if out of the grid
+fn word(grid: &[Row], y: usize, dy: isize, i: usize, di: isize) -> u64 {
+    let (Some(y), Some(i)) = (y.checked_add_signed(dy), i.checked_add_signed(di)) else {
+        return 0;
+    };
+    grid.get(y).and_then(|row| row.get(i)).copied().unwrap_or(0)
+}
+
+/// Word `i` of row `y` with bit x holding what was at `x - n`
+fn west(grid: &[Row], y: usize, dy: isize, i: usize, n: u32) -> u64 {
+    (word(grid, y, dy, i, 0) << n) | (word(grid, y, dy, i, -1) >> (64 - n))
+}
+
+/// Word `i` of row `y` with bit x holding what was at `x + n`
+fn east(grid: &[Row], y: usize, dy: isize, i: usize, n: u32) -> u64 {
+    (word(grid, y, dy, i, 0) >> n) | (word(grid, y, dy, i, 1) << (64 - n))
+}
+
+#[derive(Debug, Clone)]
+pub struct BitSimulation {
+    /// Position of bit 0 of row 0
+    origin: Xy,
+    rows: Vec<Row>,
+    /// Rotation of [`MOVES`], so that it only has N, S, W and E
+    moves: Vec<Xy>,
+    pub round: usize,
+    /// Proposals of the current round, indexed by direction N, S, W, E;
+    /// kept across rounds to reuse the allocation
+    props: Vec<Vec<Row>>,
+}
+
+impl BitSimulation {
+    pub fn new(elves: &HashSet<Xy>) -> BitSimulation {
+        BitSimulation::layout(elves, MOVES.to_vec(), 0)
+    }
+    fn layout(elves: &HashSet<Xy>, moves: Vec<Xy>, round: usize) -> BitSimulation {
+        let (min, max) = bounding_box(elves).unwrap_or_default();
+        let origin = min - Xy::new([MARGIN, MARGIN]);
+        let size = max - min + Xy::new([2 * MARGIN + 1, 2 * MARGIN + 1]);
+        let words = (size.x() as usize).div_ceil(64);
+        let mut rows = vec![vec![0; words]; size.y() as usize];
+        for elf in elves {
+            let p = *elf - origin;
+            let (x, y) = (p.x() as usize, p.y() as usize);
+            rows[y][x / 64] |= 1 << (x % 64);
+        }
+        let props = vec![vec![vec![0; words]; rows.len()]; 4];
+        BitSimulation {
+            origin,
+            rows,
+            moves,
+            round,
+            props,
+        }
+    }
+    /// Directions of the moves, in the order they are considered
+    pub fn moves(&self) -> &[Xy] {
+        &self.moves
+    }
+    pub fn elves(&self) -> HashSet<Xy> {
+        let mut elves = HashSet::<Xy>::new();
+        for (y, row) in self.rows.iter().enumerate() {
+            for (i, w) in row.iter().enumerate() {
+                let mut w = *w;
+                while w != 0 {
+                    let x = i * 64 + w.trailing_zeros() as usize;
+                    elves.insert(self.origin + Xy::new([x as i32, y as i32]));
+                    w &= w - 1;
+                }
+            }
+        }
+        elves
+    }
+    /// Checks if an elf is at the border, where its neighbours are lost
+    fn at_border(&self) -> bool {
+        let last = self.rows.len() - 1;
+        self.rows[0]
+            .iter()
+            .chain(self.rows[last].iter())
+            .any(|w| *w != 0)
+            || self
+                .rows
+                .iter()
+                .any(|row| row[0] & 1 != 0 || row[row.len() - 1] >> 63 != 0)
+    }
+    /// Evaluates a round; returns false if no elf moved
+    pub fn step(&mut self) -> bool {
+        if self.at_border() {
+            *self = BitSimulation::layout(&self.elves(), self.moves.clone(), self.round);
+        }
+        self.round += 1;
+        let grid = &self.rows;
+        let (height, words) = (grid.len(), grid[0].len());
+        // First half: proposals; every direction is in the moves, so all
+        // of the buffer gets overwritten
+        let props = &mut self.props;
+        for y in 0..height {
+            for i in 0..words {
+                let c = grid[y][i];
+                let n = word(grid, y, -1, i, 0) | west(grid, y, -1, i, 1) | east(grid, y, -1, i, 1);
+                let s = word(grid, y, 1, i, 0) | west(grid, y, 1, i, 1) | east(grid, y, 1, i, 1);
+                let w = west(grid, y, -1, i, 1) | west(grid, y, 0, i, 1) | west(grid, y, 1, i, 1);
+                let e = east(grid, y, -1, i, 1) | east(grid, y, 0, i, 1) | east(grid, y, 1, i, 1);
+                let mut remaining = c & (n | s | w | e);
+                for mv in &self.moves {
+                    let (dir, blocked) = match *mv {
+                        N => (0, n),
+                        S => (1, s),
+                        W => (2, w),
+                        E => (3, e),
+                        _ => unreachable!("invalid move {:?}", mv),
+                    };
+                    props[dir][y][i] = remaining & !blocked;
+                    remaining &= blocked;
+                }
+            }
+        }
+        // Second half: moves; only opposite proposals can collide
+        let (pn, ps, pw, pe) = (&props[0], &props[1], &props[2], &props[3]);
+        let mut moved = false;
+        let mut next = vec![vec![0_u64; words]; height];
+        for y in 0..height {
+            for i in 0..words {
+                let from_s = word(pn, y, 1, i, 0);
+                let from_n = word(ps, y, -1, i, 0);
+                let from_e = east(pw, y, 0, i, 1);
+                let from_w = west(pe, y, 0, i, 1);
+                let arrived = (from_s ^ from_n) | (from_e ^ from_w);
+                let proposed = pn[y][i] | ps[y][i] | pw[y][i] | pe[y][i];
+                let bounced = (pn[y][i] & word(ps, y, -2, i, 0))
+                    | (ps[y][i] & word(pn, y, 2, i, 0))
+                    | (pw[y][i] & west(pe, y, 0, i, 2))
+                    | (pe[y][i] & east(pw, y, 0, i, 2));
+                next[y][i] = (grid[y][i] & !proposed) | bounced | arrived;
+                moved |= arrived != 0;
+            }
+        }
+        self.rows = next;
+        let mv = self.moves.remove(0);
+        self.moves.push(mv);
+        moved
+    }
+}
+
+#[test]
+fn test_matches_evaluate_round() -> Result<()> {
+    for example in [EXAMPLE, EXAMPLE1] {
+        let input = parser::parse(example.as_bytes())?;
+        let mut elves = elves_from_input(&input);
+        let mut moves = MOVES.to_vec();
+        let mut bitsim = BitSimulation::new(&elves);
+        loop {
+            let moved = evaluate_round(&mut elves, &mut moves)?;
+            assert_eq!(bitsim.step(), moved);
+            assert_eq!(bitsim.elves(), elves);
+            assert_eq!(bitsim.moves(), moves);
+            if !moved {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_grow() {
+    // A long line of elves spreads beyond the initial margin
+    let elves = (0..100).map(|x| Xy::new([x, 0])).collect::<HashSet<_>>();
+    let mut sim = Simulation::new(elves.clone());
+    let mut bitsim = BitSimulation::new(&elves);
+    for _ in 0..(2 * MARGIN) {
+        assert_eq!(bitsim.step(), sim.step());
+        assert_eq!(bitsim.elves(), sim.elves);
+    }
+}
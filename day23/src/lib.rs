@@ -2,10 +2,15 @@
 // This file is subject to the terms and conditions defined in
 // file 'LICENSE', which is part of this source code package.
 
+pub use aoc::point::{bounding_box, Point, Point2};
 pub use color_eyre::{eyre::eyre, eyre::Error, Result};
-use sqrid::Qr;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod bitsim;
+pub mod rules;
 
 pub const EXAMPLE: &str = "....#..
 ..###.#
@@ -69,57 +74,183 @@ fn test() -> Result<()> {
 
 // Solving:
 
-// pub type Sqrid = sqrid::sqrid_create!(12, 12, false); // example1
-// pub type Sqrid = sqrid::sqrid_create!(14, 12, false); // example
-pub type Sqrid = sqrid::sqrid_create!(u16::MAX, u16::MAX, false);
-pub type Qa = sqrid::qa_create!(Sqrid);
-
-pub fn evaluate_round(elves: &mut HashSet<Qa>, moves: &mut Vec<Qr>) -> Result<bool> {
-    // First half:
-    let mut count = HashMap::<Qa, i32>::default();
-    let mut active = HashMap::<Qa, Qa>::new();
-    let mut moved = false;
-    for elf in elves.iter() {
-        for qr in Qr::iter::<true>() {
-            if let Ok(look) = *elf + qr {
-                if elves.contains(&look) {
-                    if let Some(mv_qr) = moves.iter().copied().find(|qr| {
-                        let mut qr = *qr + Qr::NW;
-                        !(0..3).any(|_| {
-                            let look = (*elf + qr).unwrap();
-                            qr += Qr::NE;
-                            elves.contains(&look)
-                        })
-                    }) {
-                        // elf is active, will move to dst
-                        let dst = (*elf + mv_qr)?;
-                        active.insert(*elf, dst);
-                        *count.entry(dst).or_insert(0) += 1;
-                        break;
-                    }
-                }
+pub type Xy = Point2<i32>;
+
+pub const N: Xy = Point([0, -1]);
+pub const S: Xy = Point([0, 1]);
+pub const W: Xy = Point([-1, 0]);
+pub const E: Xy = Point([1, 0]);
+
+/// Initial order of the moves considered by the elves
+pub const MOVES: [Xy; 4] = [N, S, W, E];
+
+pub fn elves_from_input(input: &[Vec<Cell>]) -> HashSet<Xy> {
+    let mut elves = HashSet::<Xy>::new();
+    for (y, line) in input.iter().enumerate() {
+        for (x, cell) in line.iter().enumerate() {
+            if *cell == Cell::Elf {
+                elves.insert(Xy::new([x as i32, y as i32]));
             }
         }
     }
-    // Second half, moves:
-    for (elf, dst) in active {
-        if count.get(&dst) == Some(&1) {
-            elves.remove(&elf);
-            elves.insert(dst);
-            moved = true;
+    elves
+}
+
+/// Evaluates a round with the puzzle rules, considering the moves in the
+/// given order
+pub fn evaluate_round(elves: &mut HashSet<Xy>, moves: &mut Vec<Xy>) -> Result<bool> {
+    let mut rules = rules::Rules::puzzle(moves)?;
+    let moved = rules.evaluate_round(elves);
+    *moves = rules.dirs();
+    Ok(moved)
+}
+
+/// Empty ground tiles in the smallest rectangle containing the elves
+pub fn empty_tiles(elves: &HashSet<Xy>) -> i32 {
+    bounding_box(elves).map_or(0, |(min, max)| {
+        let size = max - min + Xy::new([1, 1]);
+        size.x() * size.y() - elves.len() as i32
+    })
+}
+
+/// Elves with the rules they follow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    pub elves: HashSet<Xy>,
+    pub rules: rules::Rules,
+    /// Number of rounds evaluated so far
+    pub round: usize,
+}
+
+impl Simulation {
+    pub fn new(elves: HashSet<Xy>) -> Simulation {
+        Simulation::with_rules(elves, rules::Rules::default())
+    }
+    pub fn with_rules(elves: HashSet<Xy>, rules: rules::Rules) -> Simulation {
+        Simulation {
+            elves,
+            rules,
+            round: 0,
+        }
+    }
+    pub fn from_input(input: &[Vec<Cell>]) -> Simulation {
+        Simulation::new(elves_from_input(input))
+    }
+    /// Evaluates a round; returns false if no elf moved
+    pub fn step(&mut self) -> bool {
+        self.round += 1;
+        self.rules.evaluate_round(&mut self.elves)
+    }
+    pub fn bounding_box(&self) -> Option<(Xy, Xy)> {
+        bounding_box(&self.elves)
+    }
+    pub fn empty_tiles(&self) -> i32 {
+        empty_tiles(&self.elves)
+    }
+    /// Draws the smallest rectangle containing the elves
+    pub fn render(&self) -> String {
+        let Some((min, max)) = self.bounding_box() else {
+            return String::new();
+        };
+        let mut s = String::new();
+        for y in min.y()..=max.y() {
+            for x in min.x()..=max.x() {
+                s.push(if self.elves.contains(&Xy::new([x, y])) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            s.push('\n');
         }
+        s
     }
-    let qr = moves.remove(0);
-    moves.push(qr);
-    Ok(moved)
 }
 
-pub type GridD = sqrid::grid_create!(Sqrid, char);
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
 
-pub fn debug(elves: &HashSet<Qa>) {
-    let mut g = GridD::repeat('.');
-    for e in elves {
-        g[e] = '#';
+#[test]
+fn test_simulation() -> Result<()> {
+    let input = parser::parse(EXAMPLE1.as_bytes())?;
+    let mut sim = Simulation::from_input(&input);
+    assert_eq!(sim.to_string(), "##\n#.\n..\n##\n");
+    assert!(sim.step());
+    assert_eq!(sim.to_string(), "##\n..\n#.\n.#\n#.\n");
+    assert_eq!(sim.rules.dirs(), vec![S, W, E, N]);
+    assert!(sim.step());
+    assert!(sim.step());
+    assert_eq!(
+        sim.to_string(),
+        "..#..\n....#\n#....\n....#\n.....\n..#..\n"
+    );
+    assert!(!sim.step());
+    assert_eq!(sim.round, 4);
+    assert_eq!(sim.empty_tiles(), 25);
+    Ok(())
+}
+
+/// Common interface of the simulation backends
+pub trait Diffusion {
+    /// Evaluates a round; returns false if no elf moved
+    fn step(&mut self) -> bool;
+    fn round(&self) -> usize;
+    fn elves(&self) -> HashSet<Xy>;
+}
+
+impl Diffusion for Simulation {
+    fn step(&mut self) -> bool {
+        Simulation::step(self)
+    }
+    fn round(&self) -> usize {
+        self.round
+    }
+    fn elves(&self) -> HashSet<Xy> {
+        self.elves.clone()
+    }
+}
+
+impl Diffusion for bitsim::BitSimulation {
+    fn step(&mut self) -> bool {
+        bitsim::BitSimulation::step(self)
+    }
+    fn round(&self) -> usize {
+        self.round
+    }
+    fn elves(&self) -> HashSet<Xy> {
+        bitsim::BitSimulation::elves(self)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// [`Simulation`], with the elves in a `HashSet`
+    #[default]
+    Sparse,
+    /// [`bitsim::BitSimulation`], with the elves in rows of bits
+    Bitset,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sparse" => Ok(Backend::Sparse),
+            "bitset" => Ok(Backend::Bitset),
+            _ => Err(eyre!("unknown backend {}", s)),
+        }
+    }
+}
+
+impl Backend {
+    pub fn simulation(&self, input: &[Vec<Cell>]) -> Box<dyn Diffusion> {
+        let elves = elves_from_input(input);
+        match self {
+            Backend::Sparse => Box::new(Simulation::new(elves)),
+            Backend::Bitset => Box::new(bitsim::BitSimulation::new(&elves)),
+        }
     }
-    eprintln!("{}", g);
 }
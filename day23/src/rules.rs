@@ -0,0 +1,196 @@
+// Copyright (C) 2022 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+//! Movement rules of the elves, configurable to explore variants.
+
+use super::*;
+
+/// A move an elf can propose
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Direction of the move
+    pub dir: Xy,
+    /// Cells, relative to the elf, that must be empty to propose it;
+    /// the destination is always checked
+    pub checks: Vec<Xy>,
+}
+
+impl Rule {
+    /// Puzzle rule: the 3 cells in the direction of the move are checked
+    pub fn puzzle(dir: Xy) -> Rule {
+        let side = Xy::new([dir.y(), dir.x()]);
+        Rule {
+            dir,
+            checks: (-1..=1).map(|k| dir + side * k).collect(),
+        }
+    }
+}
+
+/// What happens when more than one elf proposes the same tile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    /// None of the elves moves
+    #[default]
+    AllStay,
+    /// The first elf in reading order moves, the others stay
+    FirstWins,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Rules in the order they are considered; never empty, and with
+    /// each direction at most once
+    order: Vec<Rule>,
+    /// Cells, relative to the elf, that make it propose a move when any
+    /// of them has an elf
+    pub crowd: Vec<Xy>,
+    /// Whether the first rule goes to the end of the order every round
+    pub rotate: bool,
+    pub collision: Collision,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::puzzle(&MOVES).expect("puzzle moves are valid")
+    }
+}
+
+impl Rules {
+    /// Creates the rules, failing if `order` is empty or has a repeated
+    /// direction
+    pub fn new(
+        order: Vec<Rule>,
+        crowd: Vec<Xy>,
+        rotate: bool,
+        collision: Collision,
+    ) -> Result<Rules> {
+        if order.is_empty() {
+            return Err(eyre!("no rules to consider"));
+        }
+        let mut dirs = HashSet::<Xy>::new();
+        if let Some(rule) = order.iter().find(|rule| !dirs.insert(rule.dir)) {
+            return Err(eyre!("direction {:?} is repeated", rule.dir));
+        }
+        Ok(Rules {
+            order,
+            crowd,
+            rotate,
+            collision,
+        })
+    }
+
+    /// Puzzle rules, with the moves considered in the given order
+    pub fn puzzle(dirs: &[Xy]) -> Result<Rules> {
+        Rules::new(
+            dirs.iter().copied().map(Rule::puzzle).collect(),
+            Xy::iter_dirs_diag().collect(),
+            true,
+            Collision::AllStay,
+        )
+    }
+
+    /// Rules in the order they are considered
+    pub fn order(&self) -> &[Rule] {
+        &self.order
+    }
+
+    /// Directions of the rules, in the order they are considered
+    pub fn dirs(&self) -> Vec<Xy> {
+        self.order.iter().map(|rule| rule.dir).collect()
+    }
+
+    /// Evaluates a round; returns false if no elf moved
+    pub fn evaluate_round(&mut self, elves: &mut HashSet<Xy>) -> bool {
+        // First half, proposals:
+        let mut proposals = HashMap::<Xy, Vec<Xy>>::new();
+        for elf in elves.iter() {
+            if !self.crowd.iter().any(|c| elves.contains(&(*elf + *c))) {
+                continue;
+            }
+            if let Some(rule) = self.order.iter().find(|rule| {
+                !elves.contains(&(*elf + rule.dir))
+                    && !rule.checks.iter().any(|c| elves.contains(&(*elf + *c)))
+            }) {
+                proposals.entry(*elf + rule.dir).or_default().push(*elf);
+            }
+        }
+        // Second half, moves:
+        let mut moved = false;
+        for (dst, srcs) in proposals {
+            let src = match self.collision {
+                Collision::AllStay if srcs.len() > 1 => continue,
+                Collision::AllStay => srcs[0],
+                Collision::FirstWins => *srcs.iter().min_by_key(|xy| (xy.y(), xy.x())).unwrap(),
+            };
+            elves.remove(&src);
+            elves.insert(dst);
+            moved = true;
+        }
+        if self.rotate {
+            self.order.rotate_left(1);
+        }
+        moved
+    }
+}
+
+#[test]
+fn test_puzzle_rule() {
+    assert_eq!(
+        Rule::puzzle(N).checks,
+        vec![Xy::new([1, -1]), Xy::new([0, -1]), Xy::new([-1, -1])]
+    );
+    assert_eq!(
+        Rule::puzzle(E).checks,
+        vec![Xy::new([1, -1]), Xy::new([1, 0]), Xy::new([1, 1])]
+    );
+}
+
+#[test]
+fn test_variants() -> Result<()> {
+    let input = parser::parse(EXAMPLE1.as_bytes())?;
+    // Without rotation
+    let mut sim = Simulation::with_rules(
+        elves_from_input(&input),
+        Rules {
+            rotate: false,
+            ..Rules::default()
+        },
+    );
+    sim.step();
+    assert_eq!(sim.rules.dirs(), MOVES.to_vec());
+    // When the elves at the bottom collide, the upper one moves
+    let mut sim = Simulation::with_rules(
+        elves_from_input(&input),
+        Rules {
+            collision: Collision::FirstWins,
+            ..Rules::default()
+        },
+    );
+    sim.step();
+    assert_eq!(sim.to_string(), "##\n..\n..\n##\n#.\n");
+    // Elves that only move east, and only if there's an elf to the west
+    let mut sim = Simulation::with_rules(
+        elves_from_input(&input),
+        Rules::new(
+            vec![Rule {
+                dir: E,
+                checks: vec![],
+            }],
+            vec![W],
+            false,
+            Collision::AllStay,
+        )?,
+    );
+    sim.step();
+    assert_eq!(sim.to_string(), "#.#\n#..\n...\n#.#\n");
+    Ok(())
+}
+
+#[test]
+fn test_new() -> Result<()> {
+    assert!(Rules::puzzle(&[]).is_err());
+    assert!(Rules::puzzle(&[N, S, N]).is_err());
+    assert_eq!(Rules::puzzle(&[E, W])?.dirs(), vec![E, W]);
+    Ok(())
+}
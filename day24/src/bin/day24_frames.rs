@@ -0,0 +1,36 @@
+// Copyright (C) 2023 Leandro Lisboa Penz <lpenz@lpenz.org>
+// This file is subject to the terms and conditions defined in
+// file 'LICENSE', which is part of this source code package.
+
+use std::env;
+use std::io::{stdin, BufRead};
+
+use day24::*;
+
+fn process(bufin: impl BufRead, roundtrip: bool) -> Result<Vec<String>> {
+    let input = parser::parse(bufin)?;
+    let params = Params::new(input)?;
+    let path = if roundtrip {
+        let waypoints = [params.start, params.target, params.start, params.target];
+        params.trip(0, &waypoints, true)?.path
+    } else {
+        params.bfs(0, params.start, params.target)?
+    };
+    params.frames(&path)
+}
+
+#[test]
+fn test() -> Result<()> {
+    assert_eq!(process(EXAMPLE.as_bytes(), false)?.len(), 19);
+    assert_eq!(process(EXAMPLE.as_bytes(), true)?.len(), 55);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let roundtrip = env::args().nth(1).as_deref() == Some("--roundtrip");
+    for frame in process(stdin().lock(), roundtrip)? {
+        println!("{}", frame);
+    }
+    Ok(())
+}
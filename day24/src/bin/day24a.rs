@@ -8,9 +8,9 @@ use day24::*;
 
 fn process(bufin: impl BufRead) -> Result<Turn> {
     let input = parser::parse(bufin)?;
-    let mut params = Params::new(input)?;
+    let params = Params::new(input)?;
     // Good ol' BFS
-    params.bfs(0, params.start, params.target)
+    Ok(params.bfs(0, params.start, params.target)?.end_turn())
 }
 
 #[test]
@@ -8,12 +8,10 @@ use day24::*;
 
 fn process(bufin: impl BufRead) -> Result<Turn> {
     let input = parser::parse(bufin)?;
-    let mut params = Params::new(input)?;
-    // Good ol' BFS
-    let t0 = params.bfs(0, params.start, params.target)?;
-    let t1 = params.bfs(t0, params.target, params.start)?;
-    let t2 = params.bfs(t1, params.start, params.target)?;
-    Ok(t2)
+    let params = Params::new(input)?;
+    let waypoints = [params.start, params.target, params.start, params.target];
+    let trip = params.trip(0, &waypoints, true)?;
+    Ok(trip.total())
 }
 
 #[test]
@@ -17,7 +17,7 @@ pub const EXAMPLE: &str = "#.######
 pub type Sqrid = sqrid::sqrid_create!(122, 27, false);
 pub type Qa = sqrid::qa_create!(Sqrid);
 pub type Grid = sqrid::grid_create!(Sqrid, Cell);
-pub type GridDebug = sqrid::grid_create!(Sqrid, char);
+#[allow(clippy::manual_div_ceil)]
 pub type Gridbool = sqrid::gridbool_create!(Sqrid);
 pub type Qr = sqrid::Qr;
 
@@ -114,7 +114,7 @@ fn test_bliz() -> Result<()> {
     let bliz = |x, y, qr| Bliz {
         id: 0,
         qa0: Qa::new(x, y).unwrap(),
-        qr: qr,
+        qr,
     };
     // Direct
     assert_eq!(bliz(1, 1, Qr::E).qa_at(&botright, 1)?, Qa::new(2, 1)?);
@@ -139,12 +139,112 @@ impl From<(usize, Qa, Qr)> for Bliz {
     }
 }
 
+fn gcd(a: Turn, b: Turn) -> Turn {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Blizzard occupancy of the valley, as bitmasks of the blizzards at
+/// turn 0 for each row and column of the interior
+///
+/// Blizzards in a row come back to the same position every `width`
+/// turns, and the ones in a column every `height` turns, so the
+/// occupancy at any turn is a rotation of these masks.
+#[derive(Default, Debug, Clone)]
+pub struct Valley {
+    pub width: Turn,
+    pub height: Turn,
+    /// Indexed by row, bit i is the column i
+    east: Vec<u128>,
+    west: Vec<u128>,
+    /// Indexed by column, bit j is the row j
+    south: Vec<u128>,
+    north: Vec<u128>,
+}
+
+impl Valley {
+    pub fn new(botright: &Qa, blizs: &[Bliz]) -> Result<Valley> {
+        let (width, height) = (
+            botright.tuple().0 as Turn - 1,
+            botright.tuple().1 as Turn - 1,
+        );
+        if width > 128 || height > 128 {
+            return Err(eyre!("valley {}x{} is too big", width, height));
+        }
+        let mut valley = Valley {
+            width,
+            height,
+            east: vec![0; height as usize],
+            west: vec![0; height as usize],
+            south: vec![0; width as usize],
+            north: vec![0; width as usize],
+        };
+        for b in blizs {
+            let (x, y) = (b.qa0.tuple().0 as Turn, b.qa0.tuple().1 as Turn);
+            if x < 1 || x > width || y < 1 || y > height {
+                return Err(eyre!("blizzard {:?} is outside of the valley", b));
+            }
+            let (i, j) = (x as usize - 1, y as usize - 1);
+            match b.qr {
+                Qr::E => valley.east[j] |= 1 << i,
+                Qr::W => valley.west[j] |= 1 << i,
+                Qr::S => valley.south[i] |= 1 << j,
+                Qr::N => valley.north[i] |= 1 << j,
+                _ => return Err(eyre!("invalid blizzard direction")),
+            }
+        }
+        Ok(valley)
+    }
+
+    /// Number of turns after which all blizzards are back where they
+    /// started: lcm(width, height)
+    pub fn period(&self) -> Turn {
+        self.width / gcd(self.width, self.height) * self.height
+    }
+
+    /// Checks if there is a blizzard at `qa` in `turn`
+    pub fn any_at(&self, turn: Turn, qa: Qa) -> bool {
+        let (x, y) = (qa.tuple().0 as Turn, qa.tuple().1 as Turn);
+        if x < 1 || x > self.width || y < 1 || y > self.height {
+            return false;
+        }
+        let (i, j) = (x - 1, y - 1);
+        let (w, h) = (self.width, self.height);
+        let bit = |mask: u128, b: Turn| mask & (1 << b) != 0;
+        bit(self.east[j as usize], (i - turn).rem_euclid(w))
+            || bit(self.west[j as usize], (i + turn).rem_euclid(w))
+            || bit(self.south[i as usize], (j - turn).rem_euclid(h))
+            || bit(self.north[i as usize], (j + turn).rem_euclid(h))
+    }
+}
+
+#[test]
+fn test_valley() -> Result<()> {
+    let params = Params::new(parser::parse(EXAMPLE.as_bytes())?)?;
+    let valley = &params.valley;
+    assert_eq!((valley.width, valley.height, valley.period()), (6, 4, 12));
+    // Must match the positions of the individual blizzards
+    for turn in 0..=valley.period() {
+        for qa in Qa::iter() {
+            let expected = params
+                .blizs
+                .iter()
+                .any(|b| b.qa_at(&params.botright, turn).ok() == Some(qa));
+            assert_eq!(valley.any_at(turn, qa), expected);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Params {
     pub botright: Qa,
     pub blizs: Vec<Bliz>,
     pub walls: HashSet<Qa>,
-    pub cache: HashMap<(Turn, Qa), bool>,
+    pub valley: Valley,
     pub start: Qa,
     pub target: Qa,
 }
@@ -172,26 +272,15 @@ impl Params {
                 }
             }
         }
+        params.valley = Valley::new(&params.botright, &params.blizs)?;
         Ok(params)
     }
 
-    pub fn any_at(&mut self, turn: Turn, qa: Qa) -> bool {
-        if let Some(cached) = self.cache.get(&(turn, qa)) {
-            return *cached;
-        }
-        let result = self
-            .blizs
-            .iter()
-            .filter(|b| {
-                ((b.qr == Qr::N || b.qr == Qr::S) && b.qa0.tuple().0 == qa.tuple().0)
-                    || ((b.qr == Qr::E || b.qr == Qr::W) && b.qa0.tuple().1 == qa.tuple().1)
-            })
-            .any(|b| b.qa_at(&self.botright, turn).ok() == Some(qa));
-        self.cache.insert((turn, qa), result);
-        result
+    pub fn any_at(&self, turn: Turn, qa: Qa) -> bool {
+        self.valley.any_at(turn, qa)
     }
 
-    pub fn iter(&mut self, turn: Turn, me: Qa) -> impl Iterator<Item = (Qa, Option<Qr>)> + '_ {
+    pub fn iter(&self, turn: Turn, me: Qa) -> impl Iterator<Item = (Qa, Option<Qr>)> + '_ {
         let mut v = vec![];
         if !self.any_at(turn, me) {
             v.push((me, None));
@@ -212,56 +301,313 @@ impl Params {
         v.into_iter()
     }
 
-    pub fn debug(&self, turn: Turn, me: Qa) {
-        let blizs = self
-            .blizs
-            .iter()
-            .map(|b| {
-                (
-                    b.qa_at(&self.botright, turn).unwrap(),
-                    match b.qr {
-                        Qr::N => '^',
-                        Qr::E => '>',
-                        Qr::S => 'v',
-                        Qr::W => '<',
-                        _ => panic!("invalid qr"),
-                    },
-                )
-            })
-            .collect::<HashMap<_, _>>();
-        let g = Qa::iter()
-            .map(|qa| {
-                if self.walls.contains(&qa) {
+    /// Draws the valley at `turn` like the puzzle does, with stacked
+    /// blizzards as their count; the expedition is the `E` at the end of
+    /// `trail`, and the rest of the trail is drawn as `o` where there's
+    /// nothing else to show
+    pub fn render(&self, turn: Turn, trail: &[Qa]) -> Result<String> {
+        let me = trail.last().copied();
+        let trail = trail.iter().collect::<HashSet<_>>();
+        let mut blizs = HashMap::<Qa, Vec<Qr>>::new();
+        for b in &self.blizs {
+            blizs
+                .entry(b.qa_at(&self.botright, turn)?)
+                .or_default()
+                .push(b.qr);
+        }
+        let (width, height) = self.botright.tuple();
+        let mut s = String::new();
+        for y in 0..=height {
+            for x in 0..=width {
+                let qa = Qa::new(x, y)?;
+                s.push(if self.walls.contains(&qa) {
                     '#'
-                } else if let Some(b) = blizs.get(&qa) {
-                    *b
-                } else if qa == me {
-                    '@'
+                } else if Some(qa) == me {
+                    'E'
                 } else {
-                    ' '
-                }
-            })
-            .collect::<GridDebug>();
-        eprintln!("{}", g);
+                    match blizs.get(&qa).map(|qrs| qrs.as_slice()) {
+                        None if trail.contains(&qa) => 'o',
+                        None => '.',
+                        Some([Qr::N]) => '^',
+                        Some([Qr::E]) => '>',
+                        Some([Qr::S]) => 'v',
+                        Some([Qr::W]) => '<',
+                        Some(qrs) => char::from_digit(qrs.len() as u32, 10).unwrap_or('*'),
+                    }
+                });
+            }
+            s.push('\n');
+        }
+        Ok(s)
     }
 
-    pub fn bfs(&mut self, mut turn: Turn, start: Qa, target: Qa) -> Result<Turn> {
-        let mut nextfront = HashSet::<Qa>::default();
-        nextfront.insert(start);
-        let mut found = false;
-        while !found {
-            let front = std::mem::take(&mut nextfront);
-            turn += 1;
-            for me in &front {
-                if *me == target {
-                    found = true;
-                    break;
-                }
-                for (qa, _qropt) in self.iter(turn, *me) {
-                    nextfront.insert(qa);
+    pub fn debug(&self, turn: Turn, me: Qa) {
+        if let Ok(s) = self.render(turn, &[me]) {
+            eprintln!("{}", s);
+        }
+    }
+
+    /// Renders each turn of `path`, headed like the puzzle's walkthrough
+    pub fn frames(&self, path: &Path) -> Result<Vec<String>> {
+        let mut frames = vec![format!(
+            "Initial state:\n{}",
+            self.render(path.start_turn, &path.positions[..1])?
+        )];
+        for (i, qropt) in path.moves.iter().enumerate() {
+            let turn = path.start_turn + i as Turn + 1;
+            let action = match qropt {
+                None => "wait",
+                Some(Qr::N) => "move up",
+                Some(Qr::E) => "move right",
+                Some(Qr::S) => "move down",
+                Some(Qr::W) => "move left",
+                Some(_) => return Err(eyre!("invalid move {:?}", qropt)),
+            };
+            frames.push(format!(
+                "Minute {}, {}:\n{}",
+                turn,
+                action,
+                self.render(turn, &path.positions[..=i + 1])?
+            ));
+        }
+        Ok(frames)
+    }
+
+    /// Finds the fastest path from `start` to `target`, leaving at `turn`
+    pub fn bfs(&self, turn: Turn, start: Qa, target: Qa) -> Result<Path> {
+        Ok(self.trip(turn, &[start, target], true)?.path)
+    }
+
+    /// Finds the fastest trip that goes through all `waypoints` in order,
+    /// leaving the first one at `turn`
+    ///
+    /// States are `(leg, turn % period, position)`, as the blizzards
+    /// repeat every period; when all of them have been visited without
+    /// reaching the last waypoint, it's unreachable. If
+    /// `wait_at_waypoints` is false, the expedition can't stay put on the
+    /// waypoint it last reached; it can still wait on the other ones.
+    pub fn trip(&self, turn: Turn, waypoints: &[Qa], wait_at_waypoints: bool) -> Result<Trip> {
+        let start = *waypoints
+            .first()
+            .ok_or_else(|| eyre!("trip without waypoints"))?;
+        let period = self.valley.period();
+        // Skips the waypoints at `qa`, returning the index of the next one
+        let advance = |mut leg: usize, qa: Qa| {
+            while leg < waypoints.len() && waypoints[leg] == qa {
+                leg += 1;
+            }
+            leg
+        };
+        let mut parents = TripParents::new();
+        let mut visited = HashSet::<(usize, Turn, Qa)>::new();
+        let first = (advance(1, start), start);
+        visited.insert((first.0, turn % period, start));
+        let mut front = vec![first];
+        let mut current = turn;
+        while !front.is_empty() {
+            if let Some(last) = front.iter().find(|(leg, _)| *leg == waypoints.len()) {
+                return Ok(Trip::from_parents(
+                    &parents,
+                    period,
+                    turn,
+                    current,
+                    *last,
+                    waypoints.len(),
+                ));
+            }
+            current += 1;
+            let mut nextfront = vec![];
+            for &(leg, me) in &front {
+                for (qa, qropt) in self.iter(current, me) {
+                    // `leg` is the next waypoint, so the last one reached is
+                    // the one before it
+                    if qropt.is_none() && !wait_at_waypoints && me == waypoints[leg - 1] {
+                        continue;
+                    }
+                    let nextleg = advance(leg, qa);
+                    let key = (nextleg, current % period, qa);
+                    if visited.insert(key) {
+                        parents.insert(key, ((leg, me), qropt));
+                        nextfront.push((nextleg, qa));
+                    }
                 }
             }
+            front = nextfront;
+        }
+        Err(eyre!(
+            "trip through {:?} is unreachable leaving at turn {}",
+            waypoints,
+            turn
+        ))
+    }
+}
+
+/// Maps `(leg, turn % period, position)` to the previous `(leg, position)`
+/// and the move that got us there
+type TripParents = HashMap<(usize, Turn, Qa), ((usize, Qa), Option<Qr>)>;
+
+/// Path of the expedition; a move of `None` is a wait
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub start_turn: Turn,
+    pub moves: Vec<Option<Qr>>,
+    /// Position at each turn, starting at `start_turn`
+    pub positions: Vec<Qa>,
+}
+
+impl Path {
+    /// Turn at which the expedition arrives at the end of the path
+    pub fn end_turn(&self) -> Turn {
+        self.start_turn + self.moves.len() as Turn
+    }
+}
+
+/// Trip through a list of waypoints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trip {
+    pub path: Path,
+    /// Turn at which each waypoint was reached
+    pub arrivals: Vec<Turn>,
+}
+
+impl Trip {
+    fn from_parents(
+        parents: &TripParents,
+        period: Turn,
+        start_turn: Turn,
+        end_turn: Turn,
+        last: (usize, Qa),
+        legs: usize,
+    ) -> Trip {
+        let mut moves = vec![];
+        let mut positions = vec![last.1];
+        let mut arrivals = vec![start_turn; legs];
+        let (mut leg, mut qa) = last;
+        for turn in (start_turn + 1..=end_turn).rev() {
+            let ((prevleg, prev), qropt) = parents[&(leg, turn % period, qa)];
+            for arrival in &mut arrivals[prevleg..leg] {
+                *arrival = turn;
+            }
+            moves.push(qropt);
+            positions.push(prev);
+            (leg, qa) = (prevleg, prev);
+        }
+        moves.reverse();
+        positions.reverse();
+        Trip {
+            path: Path {
+                start_turn,
+                moves,
+                positions,
+            },
+            arrivals,
+        }
+    }
+
+    /// Time taken by the whole trip
+    pub fn total(&self) -> Turn {
+        self.path.end_turn() - self.path.start_turn
+    }
+}
+
+#[test]
+fn test_path() -> Result<()> {
+    let params = Params::new(parser::parse(EXAMPLE.as_bytes())?)?;
+    let path = params.bfs(0, params.start, params.target)?;
+    assert_eq!(path.end_turn(), 18);
+    assert_eq!(path.positions.first(), Some(&params.start));
+    assert_eq!(path.positions.last(), Some(&params.target));
+    // Replay the moves, checking for walls and blizzards
+    let mut qa = params.start;
+    for (i, qropt) in path.moves.iter().enumerate() {
+        if let Some(qr) = qropt {
+            qa = (qa + *qr)?;
         }
-        Ok(turn - 1)
+        assert_eq!(qa, path.positions[i + 1]);
+        assert!(!params.walls.contains(&qa));
+        assert!(!params.any_at(i as Turn + 1, qa));
     }
+    Ok(())
+}
+
+#[test]
+fn test_render() -> Result<()> {
+    let params = Params::new(parser::parse(EXAMPLE.as_bytes())?)?;
+    assert_eq!(params.render(0, &[])?, EXAMPLE);
+    assert_eq!(
+        params.render(1, &[params.start, Qa::new(1, 1)?])?,
+        "#o######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+"
+    );
+    let frames = params.frames(&params.bfs(0, params.start, params.target)?)?;
+    assert_eq!(frames.len(), 19);
+    assert!(frames[0].starts_with("Initial state:\n#E######\n"));
+    // The trail shows where there are no blizzards
+    assert_eq!(
+        frames[18],
+        "Minute 18, move down:
+#o######
+#>2o<.<#
+#.2v^2<#
+#>.o>2>#
+#<....>#
+######E#
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trip() -> Result<()> {
+    let params = Params::new(parser::parse(EXAMPLE.as_bytes())?)?;
+    let waypoints = [params.start, params.target, params.start, params.target];
+    let check_arrivals = |trip: &Trip| {
+        for (waypoint, arrival) in waypoints.iter().zip(&trip.arrivals) {
+            let i = (*arrival - trip.path.start_turn) as usize;
+            assert_eq!(trip.path.positions[i], *waypoint);
+        }
+    };
+    let trip = params.trip(0, &waypoints, true)?;
+    assert_eq!(trip.total(), 54);
+    assert_eq!(trip.arrivals.first(), Some(&0));
+    assert_eq!(trip.arrivals.last(), Some(&54));
+    check_arrivals(&trip);
+    let trip = params.trip(5, &waypoints, true)?;
+    assert_eq!(trip.arrivals.first(), Some(&5));
+    check_arrivals(&trip);
+    // Not being able to wait at the last waypoint reached can only make
+    // it longer
+    let trip = params.trip(0, &waypoints, false)?;
+    assert!(trip.total() >= 54);
+    check_arrivals(&trip);
+    for (i, qropt) in trip.path.moves.iter().enumerate() {
+        let turn = trip.path.start_turn + i as Turn;
+        let last = trip.arrivals.iter().rposition(|a| *a <= turn).unwrap();
+        assert!(qropt.is_some() || trip.path.positions[i] != waypoints[last]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_blizzard_in_wall() -> Result<()> {
+    let input = parser::parse("#.###\n>...#\n#...#\n###.#\n".as_bytes())?;
+    assert!(Params::new(input).is_err());
+    let input = parser::parse("#.###\n#...#\n#..v#\n###.#\n".as_bytes())?;
+    assert!(Params::new(input).is_ok());
+    let input = parser::parse("#.###\n#...#\n#...#\n###v#\n".as_bytes())?;
+    assert!(Params::new(input).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_unreachable() -> Result<()> {
+    // The only cell between start and target always has a blizzard
+    let params = Params::new(parser::parse("#.#\n#v#\n#.#\n".as_bytes())?)?;
+    assert!(params.bfs(0, params.start, params.target).is_err());
+    Ok(())
 }